@@ -0,0 +1,388 @@
+//! Cross-chain balance snapshots.
+//!
+//! Take a [`BalanceSnapshot`] of a set of [`BalanceKey`]s before and after an operation, then
+//! compare them with [`assert_diff!`](crate::assert_diff):
+//!
+//! ```ignore
+//! let chains = Chains::default().ggx(&alice).cosmos(&cosmos);
+//! let keys = vec![
+//!     BalanceKey::Asset(bob.clone(), 666),
+//!     BalanceKey::cosmos(ALICE_COSMOS_ADDRESS, "ERT"),
+//! ];
+//! let before = BalanceSnapshot::take(&chains, &keys).await;
+//! // ... transfer ...
+//! let after = BalanceSnapshot::take(&chains, &keys).await;
+//! assert_diff!(before, after, {
+//!     BalanceKey::Asset(bob, 666) => 999000,
+//!     BalanceKey::cosmos(ALICE_COSMOS_ADDRESS, "ERT") => -999000,
+//! });
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+use rust_decimal::prelude::ToPrimitive;
+use subxt::utils::AccountId32;
+
+use crate::containers::btc::BtcNodeContainer;
+use crate::containers::cosmos::CosmosContainer;
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::CurrencyId;
+
+/// A single balance which can be captured in a [`BalanceSnapshot`].
+#[derive(Debug, Clone)]
+pub enum BalanceKey {
+    /// free GGX native balance
+    Native(AccountId32),
+    /// `pallet_assets` balance of asset id
+    Asset(AccountId32, u32),
+    /// `orml_tokens` free balance
+    Token(AccountId32, CurrencyId),
+    /// DEX `user_token_infoes` amount of asset id (deposited, including reserved)
+    Dex(AccountId32, u32),
+    /// Cosmos bank balance of `denom`
+    Cosmos { address: String, denom: String },
+    /// Bitcoin Core wallet balance, in satoshi
+    BitcoinWallet(String),
+}
+
+impl BalanceKey {
+    pub fn cosmos(address: &str, denom: &str) -> Self {
+        Self::Cosmos {
+            address: address.to_string(),
+            denom: denom.to_string(),
+        }
+    }
+
+    pub fn bitcoin_wallet(name: &str) -> Self {
+        Self::BitcoinWallet(name.to_string())
+    }
+}
+
+impl Display for BalanceKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BalanceKey::Native(account) => write!(f, "ggx:native:{account}"),
+            BalanceKey::Asset(account, id) => write!(f, "ggx:asset[{id}]:{account}"),
+            BalanceKey::Token(account, currency) => write!(f, "ggx:token[{currency:?}]:{account}"),
+            BalanceKey::Dex(account, id) => write!(f, "ggx:dex[{id}]:{account}"),
+            BalanceKey::Cosmos { address, denom } => write!(f, "cosmos:{denom}:{address}"),
+            BalanceKey::BitcoinWallet(wallet) => write!(f, "btc:wallet:{wallet}"),
+        }
+    }
+}
+
+/// Chains to query balances from. Only chains used by the requested keys must be set.
+#[derive(Default, Clone, Copy)]
+pub struct Chains<'a> {
+    ggx: Option<&'a GgxNodeContainer>,
+    cosmos: Option<&'a CosmosContainer>,
+    btc: Option<&'a BtcNodeContainer>,
+}
+
+impl<'a> Chains<'a> {
+    pub fn ggx(mut self, ggx: &'a GgxNodeContainer) -> Self {
+        self.ggx = Some(ggx);
+        self
+    }
+
+    pub fn cosmos(mut self, cosmos: &'a CosmosContainer) -> Self {
+        self.cosmos = Some(cosmos);
+        self
+    }
+
+    pub fn btc(mut self, btc: &'a BtcNodeContainer) -> Self {
+        self.btc = Some(btc);
+        self
+    }
+
+    fn get_ggx(&self) -> &'a GgxNodeContainer {
+        self.ggx.expect("GGX node is not set in Chains")
+    }
+
+    fn get_cosmos(&self) -> &'a CosmosContainer {
+        self.cosmos.expect("Cosmos node is not set in Chains")
+    }
+
+    fn get_btc(&self) -> &'a BtcNodeContainer {
+        self.btc.expect("Bitcoin node is not set in Chains")
+    }
+
+    /// current value of a single balance
+    pub async fn balance_of(&self, key: &BalanceKey) -> i128 {
+        match key {
+            BalanceKey::Native(account) => {
+                let query = metadata::ggx::storage().system().account(account);
+                let info = self
+                    .get_ggx()
+                    .api()
+                    .storage()
+                    .at_latest()
+                    .await
+                    .expect("cannot get storage at latest")
+                    .fetch_or_default(&query)
+                    .await
+                    .expect("cannot get native balance");
+                info.data.free as i128
+            }
+            BalanceKey::Asset(account, id) => {
+                let query = metadata::ggx::storage().assets().account(id, account);
+                self.get_ggx()
+                    .api()
+                    .storage()
+                    .at_latest()
+                    .await
+                    .expect("cannot get storage at latest")
+                    .fetch(&query)
+                    .await
+                    .expect("cannot get asset balance")
+                    .map(|a| a.balance as i128)
+                    .unwrap_or_default()
+            }
+            BalanceKey::Token(account, currency) => {
+                let query = metadata::ggx::storage()
+                    .tokens()
+                    .accounts(account, currency);
+                self.get_ggx()
+                    .api()
+                    .storage()
+                    .at_latest()
+                    .await
+                    .expect("cannot get storage at latest")
+                    .fetch(&query)
+                    .await
+                    .expect("cannot get token balance")
+                    .map(|a| a.free as i128)
+                    .unwrap_or_default()
+            }
+            BalanceKey::Dex(account, id) => {
+                let query = metadata::ggx::storage()
+                    .dex()
+                    .user_token_infoes(account, id);
+                self.get_ggx()
+                    .api()
+                    .storage()
+                    .at_latest()
+                    .await
+                    .expect("cannot get storage at latest")
+                    .fetch(&query)
+                    .await
+                    .expect("cannot get dex balance")
+                    .map(|a| a.amount as i128)
+                    .unwrap_or_default()
+            }
+            BalanceKey::Cosmos { address, denom } => self
+                .get_cosmos()
+                .get_bank_balances_by_address(address)
+                .await
+                .expect("cannot get cosmos balances")
+                .balances
+                .iter()
+                .find(|b| &b.denom == denom)
+                .map(|b| {
+                    b.amount
+                        .to_i128()
+                        .expect("cosmos balance is not an integer")
+                })
+                .unwrap_or_default(),
+            BalanceKey::BitcoinWallet(wallet) => {
+                self.get_btc().wallet(wallet).await.balance().await.to_sat() as i128
+            }
+        }
+    }
+}
+
+/// Balances of a set of keys at some point in time.
+#[derive(Debug, Clone)]
+pub struct BalanceSnapshot {
+    balances: Vec<(BalanceKey, i128)>,
+}
+
+impl BalanceSnapshot {
+    /// query all `keys` from `chains`
+    pub async fn take(chains: &Chains<'_>, keys: &[BalanceKey]) -> Self {
+        let balances =
+            futures::future::join_all(keys.iter().map(|key| chains.balance_of(key))).await;
+
+        Self {
+            balances: keys.iter().cloned().zip(balances).collect(),
+        }
+    }
+
+    /// captured value of `key`, panics if `key` was not captured
+    pub fn get(&self, key: &BalanceKey) -> i128 {
+        let label = key.to_string();
+        self.balances
+            .iter()
+            .find(|(k, _)| k.to_string() == label)
+            .map(|(_, v)| *v)
+            .unwrap_or_else(|| panic!("balance of {label} is not in the snapshot"))
+    }
+
+    /// changes from `self` to `after`, both snapshots must contain the same keys
+    pub fn diff(&self, after: &BalanceSnapshot) -> BalanceDiff {
+        BalanceDiff {
+            changes: self
+                .balances
+                .iter()
+                .map(|(key, before)| (key.clone(), after.get(key) - before))
+                .collect(),
+        }
+    }
+}
+
+/// Changes of balances between two snapshots.
+#[derive(Debug, Clone)]
+pub struct BalanceDiff {
+    changes: Vec<(BalanceKey, i128)>,
+}
+
+impl BalanceDiff {
+    /// change of `key`, panics if `key` was not captured
+    pub fn get(&self, key: &BalanceKey) -> i128 {
+        let label = key.to_string();
+        self.changes
+            .iter()
+            .find(|(k, _)| k.to_string() == label)
+            .map(|(_, v)| *v)
+            .unwrap_or_else(|| panic!("balance of {label} is not in the diff"))
+    }
+
+    /// sum of changes of `keys`, zero means the amount is conserved between them
+    pub fn sum(&self, keys: &[BalanceKey]) -> i128 {
+        keys.iter().map(|key| self.get(key)).sum()
+    }
+
+    /// Panics unless every key from `expected` changed by exactly the given amount,
+    /// and all other keys did not change.
+    pub fn assert_eq(&self, expected: &[(BalanceKey, i128)]) {
+        let expected: Vec<(String, i128)> = expected
+            .iter()
+            .map(|(key, delta)| (key.to_string(), *delta))
+            .collect();
+
+        for (label, _) in &expected {
+            assert!(
+                self.changes.iter().any(|(k, _)| &k.to_string() == label),
+                "balance of {label} is not in the diff"
+            );
+        }
+
+        let mismatches: Vec<String> = self
+            .changes
+            .iter()
+            .filter_map(|(key, actual)| {
+                let label = key.to_string();
+                let wanted = expected
+                    .iter()
+                    .find(|(l, _)| l == &label)
+                    .map(|(_, d)| *d)
+                    .unwrap_or_default();
+                (wanted != *actual)
+                    .then(|| format!("  {label}: expected {wanted:+}, actual {actual:+}"))
+            })
+            .collect();
+
+        if !mismatches.is_empty() {
+            panic!(
+                "balance diff mismatch:\n{}\nfull diff:\n{}",
+                mismatches.join("\n"),
+                self
+            );
+        }
+    }
+}
+
+impl Display for BalanceDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (key, delta) in &self.changes {
+            writeln!(f, "  {key}: {delta:+}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Assert changes between two [`BalanceSnapshot`]s. Keys which are not listed must not change.
+///
+/// ```ignore
+/// assert_diff!(before, after, {
+///     BalanceKey::Asset(bob, 666) => 999000,
+/// });
+/// ```
+#[macro_export]
+macro_rules! assert_diff {
+    ($before:expr, $after:expr, { $($key:expr => $delta:expr),* $(,)? }) => {{
+        let expected: Vec<($crate::balances::BalanceKey, i128)> = vec![
+            $(($key, ($delta) as i128)),*
+        ];
+        $before.diff(&$after).assert_eq(&expected);
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoincore_rpc::bitcoin::Amount;
+    use testcontainers::runners::AsyncRunner;
+    use testcontainers::RunnableImage;
+
+    use super::*;
+    use crate::assert_diff;
+    use crate::containers::btc::{BtcNodeArgs, BtcNodeImage};
+
+    fn snapshot(balances: &[(&str, i128)]) -> BalanceSnapshot {
+        BalanceSnapshot {
+            balances: balances
+                .iter()
+                .map(|(wallet, v)| (BalanceKey::bitcoin_wallet(wallet), *v))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_and_conservation() {
+        let before = snapshot(&[("alice", 100), ("bob", 0), ("carol", 7)]);
+        let after = snapshot(&[("alice", 60), ("bob", 40), ("carol", 7)]);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.get(&BalanceKey::bitcoin_wallet("alice")), -40);
+        assert_eq!(
+            diff.sum(&[
+                BalanceKey::bitcoin_wallet("alice"),
+                BalanceKey::bitcoin_wallet("bob")
+            ]),
+            0
+        );
+
+        assert_diff!(before, after, {
+            BalanceKey::bitcoin_wallet("alice") => -40,
+            BalanceKey::bitcoin_wallet("bob") => 40,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "btc:wallet:carol: expected +0, actual -7")]
+    fn test_unlisted_keys_must_not_change() {
+        let before = snapshot(&[("alice", 100), ("carol", 7)]);
+        let after = snapshot(&[("alice", 60), ("carol", 0)]);
+
+        assert_diff!(before, after, {
+            BalanceKey::bitcoin_wallet("alice") => -40,
+        });
+    }
+
+    /// bridge network and cookie auth, the wallet balance must not assume host network
+    #[tokio::test]
+    async fn test_bitcoin_wallet_balance_with_cookie_auth() {
+        let args = BtcNodeArgs::default().with_cookie_auth();
+        let image = RunnableImage::from((BtcNodeImage::default(), args));
+        let btc = BtcNodeContainer::from(image.start().await);
+        let wallet = btc
+            .create_funded_wallet("alice", Amount::from_btc(50.0).unwrap())
+            .await;
+
+        let key = BalanceKey::bitcoin_wallet("alice");
+        let snapshot = BalanceSnapshot::take(&Chains::default().btc(&btc), &[key.clone()]).await;
+        assert_eq!(snapshot.get(&key), wallet.balance().await.to_sat() as i128);
+        assert!(snapshot.get(&key) > 0);
+    }
+}
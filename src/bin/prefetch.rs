@@ -0,0 +1,38 @@
+//! Lists every docker image referenced by testutil and pulls the ones missing locally.
+//!
+//! ```bash
+//! cargo run --bin prefetch           # pull missing images
+//! cargo run --bin prefetch -- --check # only verify, do not pull
+//! ```
+
+use testutil::images;
+
+#[tokio::main]
+async fn main() {
+    let check_only = std::env::args().any(|a| a == "--check");
+
+    let mut missing = vec![];
+    for image in images::all() {
+        let local = images::is_available_locally(&image)
+            .await
+            .expect("cannot check image");
+
+        if local {
+            println!("ok      {image}");
+        } else if check_only {
+            println!("missing {image}");
+            missing.push(image);
+        } else {
+            println!("pulling {image}");
+            if let Err(e) = images::pull(&image).await {
+                println!("failed  {image}: {e}");
+                missing.push(image);
+            }
+        }
+    }
+
+    if !missing.is_empty() {
+        eprintln!("{} image(s) are not available", missing.len());
+        std::process::exit(1);
+    }
+}
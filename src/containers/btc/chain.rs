@@ -0,0 +1,190 @@
+//! Chain manipulation for fork and reorg tests: invalidating blocks, building competing chains
+//! and connecting nodes to each other.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use bitcoincore_rpc::bitcoin::{
+    block::Header, blockdata::opcodes, Address, BlockHash, Network, ScriptBuf,
+};
+use bitcoincore_rpc::RpcApi;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::RunnableImage;
+
+use super::{BtcNodeContainer, BtcNodeImage};
+use crate::images;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+
+/// regtest p2p port
+pub const P2P_PORT: u16 = 18444;
+
+impl BtcNodeContainer {
+    pub async fn best_block_hash(&self) -> BlockHash {
+        self.rpc(|c| c.get_best_block_hash())
+            .await
+            .expect("cannot get best block hash")
+    }
+
+    pub async fn block_hash(&self, height: u64) -> BlockHash {
+        self.rpc(move |c| c.get_block_hash(height))
+            .await
+            .unwrap_or_else(|e| panic!("cannot get block hash at height {height}: {e}"))
+    }
+
+    pub async fn block_header(&self, hash: BlockHash) -> Header {
+        self.rpc(move |c| c.get_block_header(&hash))
+            .await
+            .unwrap_or_else(|e| panic!("cannot get block header {hash}: {e}"))
+    }
+
+    /// Mark `hash` and all its descendants as invalid, the node switches to the best valid chain.
+    pub async fn invalidate_block(&self, hash: &BlockHash) {
+        let hash = *hash;
+        log::info!("Invalidating BTC block {}", hash);
+        self.rpc(move |c| c.invalidate_block(&hash))
+            .await
+            .unwrap_or_else(|e| panic!("cannot invalidate block {hash}: {e}"))
+    }
+
+    /// Undo [`invalidate_block`](Self::invalidate_block), the node switches back if that chain
+    /// has more work.
+    pub async fn reconsider_block(&self, hash: &BlockHash) {
+        let hash = *hash;
+        log::info!("Reconsidering BTC block {}", hash);
+        self.rpc(move |c| c.reconsider_block(&hash))
+            .await
+            .unwrap_or_else(|e| panic!("cannot reconsider block {hash}: {e}"))
+    }
+
+    /// Replace the last `depth` blocks with `new_len` new blocks.
+    /// Returns the first replaced block and hashes of the new blocks.
+    ///
+    /// Replaced blocks stay invalid on this node until [`reconsider_block`](Self::reconsider_block)
+    /// is called with the first of them.
+    pub async fn reorg(&self, depth: u64, new_len: u64) -> (BlockHash, Vec<BlockHash>) {
+        let height = self.get_block_count().await;
+        assert!(
+            depth >= 1 && depth <= height,
+            "cannot reorg {depth} blocks at height {height}"
+        );
+
+        let fork_start = self.block_hash(height - depth + 1).await;
+        log::info!(
+            "Reorg of {} blocks at height {}, new chain has {} blocks",
+            depth,
+            height,
+            new_len
+        );
+        self.invalidate_block(&fork_start).await;
+
+        // mine to a fresh address, otherwise a new block may be identical to the invalidated one
+        let new_blocks = self.mine_to(new_len, &fork_address()).await;
+        (fork_start, new_blocks)
+    }
+
+    /// `ip:port` other nodes use to connect to this node
+    pub async fn p2p_address(&self) -> String {
+        format!("{}:{P2P_PORT}", self.container_ip().await)
+    }
+
+    /// Connect to `other` and wait until the connection is established.
+    pub async fn connect_peer(&self, other: &BtcNodeContainer) {
+        let address = other.p2p_address().await;
+        log::info!("Connecting BTC node to peer {}", address);
+
+        let peer = address.clone();
+        self.rpc(move |c| {
+            c.add_node(&peer)?;
+            c.onetry_node(&peer)
+        })
+        .await
+        .unwrap_or_else(|e| panic!("cannot add peer {address}: {e}"));
+
+        eventually(Duration::from_secs(30), DEFAULT_POLL_INTERVAL, || async {
+            let peers = self.peer_addresses().await;
+            if peers.contains(&address) {
+                Ok(())
+            } else {
+                Err(peers)
+            }
+        })
+        .await;
+    }
+
+    /// Disconnect from `other` previously connected with [`connect_peer`](Self::connect_peer).
+    pub async fn disconnect_peer(&self, other: &BtcNodeContainer) {
+        let address = other.p2p_address().await;
+        log::info!("Disconnecting BTC node from peer {}", address);
+
+        let peer = address.clone();
+        self.rpc(move |c| {
+            c.remove_node(&peer)?;
+            c.disconnect_node(&peer)
+        })
+        .await
+        .unwrap_or_else(|e| panic!("cannot disconnect peer {address}: {e}"));
+
+        eventually(Duration::from_secs(30), DEFAULT_POLL_INTERVAL, || async {
+            let peers = self.peer_addresses().await;
+            if peers.contains(&address) {
+                Err(peers)
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+    }
+
+    /// addresses of connected peers
+    pub async fn peer_addresses(&self) -> Vec<String> {
+        self.rpc(|c| c.get_peer_info())
+            .await
+            .expect("cannot get peer info")
+            .into_iter()
+            .map(|p| p.addr)
+            .collect()
+    }
+
+    /// Wait until this node and `other` have the same best block.
+    pub async fn wait_for_sync_with(
+        &self,
+        other: &BtcNodeContainer,
+        timeout: Duration,
+    ) -> BlockHash {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let (ours, theirs) = futures::join!(self.best_block_hash(), other.best_block_hash());
+            if ours == theirs {
+                Ok(ours)
+            } else {
+                Err((ours, theirs))
+            }
+        })
+        .await
+    }
+}
+
+/// Start two unconnected regtest nodes on the default bridge network, so that competing chains
+/// can be built on each of them before [`connect_peer`](BtcNodeContainer::connect_peer).
+pub async fn start_btc_pair() -> (BtcNodeContainer, BtcNodeContainer) {
+    log::info!("Starting Bitcoin pair");
+    let image = BtcNodeImage::default();
+    images::require(&image).await;
+
+    let (a, b) = futures::join!(
+        RunnableImage::from(image.clone()).start(),
+        RunnableImage::from(image).start()
+    );
+    (BtcNodeContainer::from(a), BtcNodeContainer::from(b))
+}
+
+/// Regtest P2WSH(<n> OP_DROP OP_TRUE) address, unique within the test process.
+fn fork_address() -> Address {
+    static NONCE: AtomicI64 = AtomicI64::new(1);
+
+    let script = ScriptBuf::builder()
+        .push_int(NONCE.fetch_add(1, Ordering::Relaxed))
+        .push_opcode(opcodes::all::OP_DROP)
+        .push_opcode(opcodes::OP_TRUE)
+        .into_script();
+    Address::p2wsh(&script, Network::Regtest)
+}
@@ -0,0 +1,143 @@
+//! Mempool and fee market simulation: many transactions at different fee rates, fee
+//! prioritisation, blocks with a chosen set of transactions and RBF fee bumps.
+
+use std::collections::{HashMap, HashSet};
+
+use bitcoincore_rpc::bitcoin::{Address, Amount, BlockHash, OutPoint, Txid};
+use bitcoincore_rpc::json::GetMempoolEntryResult;
+use bitcoincore_rpc::RpcApi;
+use serde_json::{json, Value};
+
+use super::{default_mining_address, BtcNodeContainer, BtcWallet};
+
+/// value of every transaction created by [`BtcNodeContainer::fill_mempool`]
+pub const FILL_AMOUNT: Amount = Amount::from_sat(10_000);
+
+impl BtcNodeContainer {
+    /// Broadcast `count` transactions from `wallet` to itself, fee rates in sat/vB are taken from
+    /// `fee_rates` in a round robin. Every transaction spends its own confirmed output, so they do
+    /// not hit the mempool chain limit. Transactions are replaceable.
+    pub async fn fill_mempool(
+        &self,
+        wallet: &BtcWallet,
+        count: usize,
+        fee_rates: &[u64],
+    ) -> Vec<Txid> {
+        assert!(!fee_rates.is_empty(), "no fee rates given");
+        log::info!("Filling mempool with {} transactions", count);
+
+        // split wallet funds into `count` confirmed outputs, one per transaction
+        let max_fee = Amount::from_sat(fee_rates.iter().max().unwrap() * 200);
+        let mut split = wallet.tx();
+        for _ in 0..count {
+            split = split.pay(&wallet.new_address().await, FILL_AMOUNT + max_fee);
+        }
+        let split = split.send().await;
+        self.mine(1).await;
+
+        let mut txids = Vec::with_capacity(count);
+        for i in 0..count {
+            // split outputs go first, the change is appended after them
+            let txid = wallet
+                .tx()
+                .spend(OutPoint::new(split, i as u32))
+                .pay(&wallet.new_address().await, FILL_AMOUNT)
+                .fee_rate(fee_rates[i % fee_rates.len()])
+                .replaceable()
+                .send()
+                .await;
+            txids.push(txid);
+        }
+        txids
+    }
+
+    pub async fn mempool_txids(&self) -> Vec<Txid> {
+        self.rpc(|c| c.get_raw_mempool())
+            .await
+            .expect("cannot get mempool")
+    }
+
+    /// mempool entry of `txid`, `None` if it is not in the mempool
+    pub async fn mempool_entry(&self, txid: &Txid) -> Option<GetMempoolEntryResult> {
+        let txid = *txid;
+        self.rpc(move |c| c.get_mempool_entry(&txid)).await.ok()
+    }
+
+    /// `prioritisetransaction`: mine `txid` as if it paid `fee_delta` more (or less) satoshi
+    pub async fn prioritise_transaction(&self, txid: &Txid, fee_delta: i64) {
+        let args = [json!(txid.to_string()), json!(0), json!(fee_delta)];
+        self.rpc(move |c| c.call::<bool>("prioritisetransaction", &args))
+            .await
+            .unwrap_or_else(|e| panic!("cannot prioritise {txid}: {e}"));
+    }
+
+    /// `generateblock`: mine a block with exactly `txids` in this order, parents must go first
+    pub async fn mine_including(&self, txids: &[Txid]) -> BlockHash {
+        self.generate_block(&default_mining_address(), txids).await
+    }
+
+    /// Mine a block with all mempool transactions except `txids` and their descendants.
+    pub async fn mine_excluding(&self, txids: &[Txid]) -> BlockHash {
+        let mempool = self
+            .rpc(|c| c.get_raw_mempool_verbose())
+            .await
+            .expect("cannot get mempool");
+
+        let included = without_descendants(mempool, txids);
+        self.mine_including(&included).await
+    }
+
+    pub async fn generate_block(&self, address: &Address, txids: &[Txid]) -> BlockHash {
+        let args = [
+            json!(address.to_string()),
+            json!(txids.iter().map(|t| t.to_string()).collect::<Vec<_>>()),
+        ];
+        let result = self
+            .rpc(move |c| c.call::<Value>("generateblock", &args))
+            .await
+            .expect("cannot generate block");
+
+        result["hash"]
+            .as_str()
+            .and_then(|h| h.parse().ok())
+            .unwrap_or_else(|| panic!("unexpected generateblock result: {result}"))
+    }
+}
+
+impl BtcWallet {
+    /// `bumpfee`: replace `txid` with the same transaction paying `fee_rate` sat/vB, returns
+    /// the replacement. `txid` must be replaceable, see [`super::TxBuilder::replaceable`].
+    pub async fn bump_fee(&self, txid: &Txid, fee_rate: u64) -> Txid {
+        let args = [json!(txid.to_string()), json!({ "fee_rate": fee_rate })];
+        let result = self
+            .rpc(move |c| c.call::<Value>("bumpfee", &args))
+            .await
+            .unwrap_or_else(|e| panic!("cannot bump fee of {txid}: {e}"));
+
+        result["txid"]
+            .as_str()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or_else(|| panic!("unexpected bumpfee result: {result}"))
+    }
+}
+
+/// mempool `txids` in a valid block order, without `excluded` and their descendants
+fn without_descendants(
+    mempool: HashMap<Txid, GetMempoolEntryResult>,
+    excluded: &[Txid],
+) -> Vec<Txid> {
+    let mut entries: Vec<_> = mempool.into_iter().collect();
+    // a transaction has more ancestors than any of its parents
+    entries.sort_by_key(|(_, e)| e.ancestor_count);
+
+    let mut excluded: HashSet<Txid> = excluded.iter().copied().collect();
+    let mut included = vec![];
+    for (txid, entry) in entries {
+        if excluded.contains(&txid) || entry.depends.iter().any(|p| excluded.contains(p)) {
+            excluded.insert(txid);
+        } else {
+            included.push(txid);
+        }
+    }
+    included
+}
@@ -0,0 +1,120 @@
+//! Background block mining, so payments confirm and the relay advances without manual `mine`
+//! calls.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bitcoincore_rpc::bitcoin::Address;
+use bitcoincore_rpc::RpcApi;
+use tokio::task::JoinHandle;
+
+use super::{run_blocking, BtcNodeContainer};
+use crate::wait::eventually;
+
+/// Mines a block every interval until dropped.
+pub struct AutoMiner {
+    paused: Arc<AtomicBool>,
+    mined: Arc<AtomicU64>,
+    handle: JoinHandle<()>,
+}
+
+impl AutoMiner {
+    /// stop mining until [`resume`](Self::resume), e.g. to keep a transaction in the mempool
+    pub fn pause(&self) {
+        log::debug!("Pausing BTC auto miner");
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        log::debug!("Resuming BTC auto miner");
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// number of blocks mined so far
+    pub fn blocks_mined(&self) -> u64 {
+        self.mined.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for AutoMiner {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl BtcNodeContainer {
+    /// Mine a block to `address` every `interval` in background, until the returned guard is dropped.
+    pub async fn start_auto_miner(&self, interval: Duration, address: &Address) -> AutoMiner {
+        log::info!("Starting BTC auto miner, a block every {:?}", interval);
+        let client = Arc::new(self.client(None).await);
+        let address = address.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let mined = Arc::new(AtomicU64::new(0));
+
+        let handle = tokio::spawn({
+            let paused = paused.clone();
+            let mined = mined.clone();
+            async move {
+                let mut ticks = tokio::time::interval(interval);
+                // the first tick completes immediately
+                ticks.tick().await;
+                loop {
+                    ticks.tick().await;
+                    if paused.load(Ordering::SeqCst) {
+                        continue;
+                    }
+
+                    let address = address.clone();
+                    match run_blocking(client.clone(), move |c| c.generate_to_address(1, &address))
+                        .await
+                    {
+                        Ok(_) => {
+                            mined.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Err(e) => log::warn!("BTC auto miner cannot mine a block: {}", e),
+                    }
+                }
+            }
+        });
+
+        AutoMiner {
+            paused,
+            mined,
+            handle,
+        }
+    }
+
+    /// Mine a block at a time until `condition` is true, returns the number of mined blocks.
+    /// Polling starts with `interval` and backs off like [`eventually`].
+    /// Panics if the condition is not met within `timeout`.
+    pub async fn mine_until<F, Fut>(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+        mut condition: F,
+    ) -> u64
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        let mined = &AtomicU64::new(0);
+        eventually(timeout, interval, || {
+            let met = condition();
+            async move {
+                if met.await {
+                    return Ok(mined.load(Ordering::SeqCst));
+                }
+                self.mine(1).await;
+                let n = mined.fetch_add(1, Ordering::SeqCst) + 1;
+                Err(format!("condition is not met after {n} blocks"))
+            }
+        })
+        .await
+    }
+}
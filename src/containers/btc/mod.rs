@@ -0,0 +1,839 @@
+pub extern crate bitcoincore_rpc;
+
+pub mod chain;
+pub mod mempool;
+pub mod miner;
+pub mod network;
+pub mod tx;
+pub mod wallet;
+pub mod zmq;
+
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bitcoincore_rpc::bitcoin::{
+    blockdata::opcodes::OP_TRUE, Address, Amount, BlockHash, Network, ScriptBuf, Txid,
+};
+pub use bitcoincore_rpc::Client;
+use bitcoincore_rpc::{Auth, RpcApi};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{
+    core::{Image, WaitFor},
+    ContainerAsync, ImageArgs, RunnableImage,
+};
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use crate::images;
+use crate::logs::ContainerLogs;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+pub use chain::start_btc_pair;
+pub use miner::AutoMiner;
+use network::network_ip_address;
+pub use network::BtcNetwork;
+pub use tx::{FundedTx, TxBuilder};
+pub use wallet::BtcWallet;
+
+/// coinbase outputs can be spent after this many confirmations
+pub const COINBASE_MATURITY: u64 = 100;
+
+/// default port of `-zmqpubrawblock`
+pub const ZMQ_RAWBLOCK_PORT: u16 = 28332;
+
+/// default port of `-zmqpubrawtx`
+pub const ZMQ_RAWTX_PORT: u16 = 28333;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BtcNodeImage {
+    image: String,
+    tag: String,
+}
+
+impl Default for BtcNodeImage {
+    fn default() -> Self {
+        Self {
+            image: "ruimarinho/bitcoin-core".to_string(),
+            tag: "22".to_string(),
+        }
+    }
+}
+
+impl BtcNodeImage {
+    pub fn with_image(mut self, image: String) -> Self {
+        self.image = image;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: String) -> Self {
+        self.tag = tag;
+        self
+    }
+}
+
+impl Image for BtcNodeImage {
+    type Args = BtcNodeArgs;
+
+    fn name(&self) -> String {
+        self.image.clone()
+    }
+
+    fn tag(&self) -> String {
+        self.tag.clone()
+    }
+
+    fn ready_conditions(&self) -> Vec<WaitFor> {
+        vec![WaitFor::message_on_stdout("init message: Done loading")]
+    }
+
+    fn expose_ports(&self) -> Vec<u16> {
+        vec![ZMQ_RAWBLOCK_PORT, ZMQ_RAWTX_PORT]
+    }
+}
+
+/// RPC authentication of the node
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RpcAuth {
+    UserPass {
+        user: String,
+        password: String,
+    },
+    /// bitcoind generates a random password and writes it to [`COOKIE_FILE`] in the container
+    Cookie,
+}
+
+/// cookie file location in the container, used with [`RpcAuth::Cookie`]
+pub const COOKIE_FILE: &str = "/tmp/bitcoin-rpc.cookie";
+
+/// Command line of bitcoind. Defaults to regtest with `bitcoin:bitcoin` credentials,
+/// RPC on port 18443, `-txindex` and ZMQ `rawblock`/`rawtx` notifications.
+#[derive(Debug, Clone)]
+pub struct BtcNodeArgs {
+    auth: RpcAuth,
+    rpc_port: u16,
+    txindex: bool,
+    block_filter_index: bool,
+    /// `(topic, port)`, e.g. `("rawblock", 28332)`
+    zmq: Vec<(String, u16)>,
+    prune: Option<u64>,
+    extra: Vec<String>,
+}
+
+impl Default for BtcNodeArgs {
+    fn default() -> Self {
+        Self {
+            auth: RpcAuth::UserPass {
+                user: "bitcoin".to_string(),
+                password: "bitcoin".to_string(),
+            },
+            rpc_port: 18443,
+            txindex: true,
+            block_filter_index: false,
+            zmq: vec![
+                ("rawblock".to_string(), ZMQ_RAWBLOCK_PORT),
+                ("rawtx".to_string(), ZMQ_RAWTX_PORT),
+            ],
+            prune: None,
+            extra: vec![],
+        }
+    }
+}
+
+impl BtcNodeArgs {
+    pub fn with_credentials(mut self, user: &str, password: &str) -> Self {
+        self.auth = RpcAuth::UserPass {
+            user: user.to_string(),
+            password: password.to_string(),
+        };
+        self
+    }
+
+    pub fn with_cookie_auth(mut self) -> Self {
+        self.auth = RpcAuth::Cookie;
+        self
+    }
+
+    /// Without host networking the port must also be mapped, e.g. with
+    /// [`RunnableImage::with_mapped_port`](testcontainers::RunnableImage::with_mapped_port).
+    pub fn with_rpc_port(mut self, port: u16) -> Self {
+        self.rpc_port = port;
+        self
+    }
+
+    pub fn with_block_filter_index(mut self) -> Self {
+        self.block_filter_index = true;
+        self
+    }
+
+    /// `-zmqpub<topic>=tcp://0.0.0.0:<port>`, topic is one of `rawblock`, `rawtx`, `hashblock`,
+    /// `hashtx` or `sequence`. Without host networking, ports other than the default ones must
+    /// also be mapped.
+    pub fn with_zmq_pub(mut self, topic: &str, port: u16) -> Self {
+        self.zmq.retain(|(t, _)| t != topic);
+        self.zmq.push((topic.to_string(), port));
+        self
+    }
+
+    pub fn without_zmq(mut self) -> Self {
+        self.zmq.clear();
+        self
+    }
+
+    /// `-prune=<mib>`, disables `-txindex` which is incompatible with pruning
+    pub fn with_prune(mut self, mib: u64) -> Self {
+        self.prune = Some(mib);
+        self.txindex = false;
+        self
+    }
+
+    /// any other bitcoind flag, e.g. `-blocksonly`
+    pub fn with_arg(mut self, arg: &str) -> Self {
+        self.extra.push(arg.to_string());
+        self
+    }
+
+    pub fn auth(&self) -> &RpcAuth {
+        &self.auth
+    }
+
+    pub fn rpc_port(&self) -> u16 {
+        self.rpc_port
+    }
+
+    /// port of ZMQ `topic`, if it is enabled
+    pub fn zmq_port(&self, topic: &str) -> Option<u16> {
+        self.zmq.iter().find(|(t, _)| t == topic).map(|(_, p)| *p)
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "-regtest",
+            "-server",
+            "-rpcbind=0.0.0.0",
+            "-rpcallowip=0.0.0.0/0",
+            "-fallbackfee=0.0002",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        match &self.auth {
+            RpcAuth::UserPass { user, password } => {
+                args.push(format!("-rpcuser={user}"));
+                args.push(format!("-rpcpassword={password}"));
+            }
+            RpcAuth::Cookie => args.push(format!("-rpccookiefile={COOKIE_FILE}")),
+        }
+        args.push(format!("-rpcport={}", self.rpc_port));
+
+        if self.txindex {
+            args.push("-txindex".to_string());
+        }
+        if self.block_filter_index {
+            args.push("-blockfilterindex".to_string());
+        }
+        for (topic, port) in &self.zmq {
+            args.push(format!("-zmqpub{topic}=tcp://0.0.0.0:{port}"));
+        }
+        if let Some(prune) = self.prune {
+            args.push(format!("-prune={prune}"));
+        }
+        args.extend(self.extra.iter().cloned());
+        args
+    }
+}
+
+impl ImageArgs for BtcNodeArgs {
+    fn into_iterator(self) -> Box<dyn Iterator<Item = String>> {
+        Box::new(self.to_args().into_iter())
+    }
+}
+
+pub struct BtcNodeContainer {
+    container: ContainerAsync<BtcNodeImage>,
+    host_network: bool,
+    /// docker network the container is attached to, `None` means default bridge
+    network: Option<String>,
+    /// credentials read from the cookie file, with [`RpcAuth::Cookie`]
+    cookie: OnceCell<(String, String)>,
+}
+
+impl ContainerLogs for BtcNodeContainer {
+    fn container_id(&self) -> &str {
+        self.container.id()
+    }
+
+    fn log_name(&self) -> &str {
+        "bitcoin"
+    }
+}
+
+impl Drop for BtcNodeContainer {
+    fn drop(&mut self) {
+        crate::logs::forget(self.container_id());
+    }
+}
+
+impl BtcNodeContainer {
+    /// wraps the container and starts capturing its logs
+    pub fn from(container: ContainerAsync<BtcNodeImage>) -> Self {
+        Self::from_inner(container, false)
+    }
+
+    pub fn from_with_host_network(container: ContainerAsync<BtcNodeImage>) -> Self {
+        Self::from_inner(container, true)
+    }
+
+    /// wraps the container started on docker network `network` (see [`testcontainers::RunnableImage::with_network`])
+    pub fn from_with_network(container: ContainerAsync<BtcNodeImage>, network: &str) -> Self {
+        let mut result = Self::from_inner(container, false);
+        result.network = Some(network.to_string());
+        result
+    }
+
+    fn from_inner(container: ContainerAsync<BtcNodeImage>, host_network: bool) -> Self {
+        let result = Self {
+            container,
+            host_network,
+            network: None,
+            cookie: OnceCell::new(),
+        };
+        result.logs();
+        result
+    }
+
+    pub fn container(&self) -> &ContainerAsync<BtcNodeImage> {
+        &self.container
+    }
+
+    /// configured command line of the node
+    pub fn args(&self) -> &BtcNodeArgs {
+        self.container.image_args()
+    }
+
+    /// RPC port on the host
+    pub async fn get_rpc_port(&self) -> u16 {
+        let port = self.args().rpc_port();
+        if self.host_network {
+            port
+        } else {
+            self.container.get_host_port_ipv4(port).await
+        }
+    }
+
+    pub async fn get_rpc_url(&self) -> String {
+        format!("http://{}:{}", self.get_host(), self.get_rpc_port().await)
+    }
+
+    /// panics with cookie auth, use [`credentials`](Self::credentials) instead
+    pub fn get_username(&self) -> String {
+        self.user_pass().0
+    }
+
+    /// panics with cookie auth, use [`credentials`](Self::credentials) instead
+    pub fn get_password(&self) -> String {
+        self.user_pass().1
+    }
+
+    fn user_pass(&self) -> (String, String) {
+        match self.args().auth() {
+            RpcAuth::UserPass { user, password } => (user.clone(), password.clone()),
+            RpcAuth::Cookie => panic!("node uses cookie auth, use credentials() instead"),
+        }
+    }
+
+    /// RPC user and password, read from the cookie file with cookie auth
+    pub async fn credentials(&self) -> (String, String) {
+        match self.args().auth() {
+            RpcAuth::UserPass { user, password } => (user.clone(), password.clone()),
+            RpcAuth::Cookie => self
+                .cookie
+                .get_or_init(|| read_cookie(self.container.id()))
+                .await
+                .clone(),
+        }
+    }
+
+    pub fn get_host(&self) -> String {
+        "127.0.0.1".to_string()
+    }
+
+    pub fn is_host_network(&self) -> bool {
+        self.host_network
+    }
+
+    /// docker network of the container, `None` for the default bridge or host network
+    pub fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    /// IP other containers use to reach this node
+    pub async fn container_ip(&self) -> String {
+        if self.host_network {
+            "127.0.0.1".to_string()
+        } else if let Some(network) = &self.network {
+            network_ip_address(self.container.id(), network).await
+        } else {
+            self.container.get_bridge_ip_address().await.to_string()
+        }
+    }
+
+    /// `ip:port` of RPC for other containers
+    pub async fn container_rpc_address(&self) -> String {
+        format!("{}:{}", self.container_ip().await, self.args().rpc_port())
+    }
+
+    /// node RPC client, works with and without host networking
+    pub async fn api(&self) -> Client {
+        self.client(None).await
+    }
+
+    pub fn api_with_host_network(&self, url_suffix: Option<&str>) -> Client {
+        self.api_with_host_port(url_suffix, "127.0.0.1", self.args().rpc_port())
+    }
+
+    /// panics with cookie auth, use [`api`](Self::api) instead
+    pub fn api_with_host_port(&self, url_suffix: Option<&str>, host: &str, port: u16) -> Client {
+        let (user, password) = self.user_pass();
+        new_client(url_suffix, host, port, user, password)
+    }
+
+    /// RPC client of this node, or of its `wallet` if set
+    async fn client(&self, wallet: Option<&str>) -> Client {
+        let suffix = wallet.map(|w| format!("wallet/{w}"));
+        let (user, password) = self.credentials().await;
+        new_client(
+            suffix.as_deref(),
+            &self.get_host(),
+            self.get_rpc_port().await,
+            user,
+            password,
+        )
+    }
+
+    /// Run blocking RPC call `f` on a node-level client without stalling the tokio runtime.
+    pub async fn rpc<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&Client) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        run_blocking(Arc::new(self.client(None).await), f).await
+    }
+
+    /// mine `n` blocks, coinbase goes to [`default_mining_address`]
+    pub async fn mine(&self, n: u64) -> Vec<BlockHash> {
+        self.mine_to(n, &default_mining_address()).await
+    }
+
+    /// mine `n` blocks with coinbase paid to `address`
+    pub async fn mine_to(&self, n: u64, address: &Address) -> Vec<BlockHash> {
+        let address = address.clone();
+        self.rpc(move |c| c.generate_to_address(n, &address))
+            .await
+            .expect("cannot generate blocks")
+    }
+
+    pub async fn get_block_count(&self) -> u64 {
+        self.rpc(|c| c.get_block_count())
+            .await
+            .expect("cannot get block count")
+    }
+
+    /// Wait until `txid` has at least `confirmations`, return hash of the block which includes it.
+    /// Blocks are not mined by this function.
+    pub async fn wait_for_confirmations(
+        &self,
+        txid: &Txid,
+        confirmations: u32,
+        timeout: Duration,
+    ) -> BlockHash {
+        let client = Arc::new(self.client(None).await);
+        let txid = *txid;
+
+        let (block_hash, confirmed) = eventually(timeout, DEFAULT_POLL_INTERVAL, || {
+            let client = client.clone();
+            async move {
+                let tx = run_blocking(client, move |c| c.get_raw_transaction_info(&txid, None))
+                    .await
+                    .map_err(|e| format!("cannot get tx {txid}: {e}"))?;
+
+                match (tx.blockhash, tx.confirmations) {
+                    (Some(hash), Some(n)) if n >= confirmations => Ok((hash, n)),
+                    (_, n) => Err(format!(
+                        "tx {txid} has {} confirmations, waiting for {confirmations}",
+                        n.unwrap_or_default()
+                    )),
+                }
+            }
+        })
+        .await;
+
+        log::info!(
+            "BTC tx {} is finalized with {} confirmations (block {})",
+            txid,
+            confirmed,
+            block_hash
+        );
+        block_hash
+    }
+}
+
+fn new_client(
+    url_suffix: Option<&str>,
+    host: &str,
+    port: u16,
+    user: String,
+    password: String,
+) -> Client {
+    let url = format!("http://{host}:{port}/{}", url_suffix.unwrap_or(""));
+    Client::new(url.as_str(), Auth::UserPass(user, password)).expect("Failed to create RPC client")
+}
+
+/// `user:password` from [`COOKIE_FILE`] of container `id`, waits until bitcoind writes it
+async fn read_cookie(id: &str) -> (String, String) {
+    let cookie = eventually(Duration::from_secs(30), DEFAULT_POLL_INTERVAL, || async {
+        let output = Command::new("docker")
+            .args(["exec", id, "cat", COOKIE_FILE])
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .expect("cannot run docker, is it installed?");
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    })
+    .await;
+
+    let (user, password) = cookie
+        .split_once(':')
+        .unwrap_or_else(|| panic!("invalid cookie file: {cookie}"));
+    (user.to_string(), password.to_string())
+}
+
+/// Start the default regtest node on the host network, where the vault and electrs expect it.
+/// Only one such node can run at a time, as its ports are fixed.
+pub async fn start_host_btc() -> BtcNodeContainer {
+    log::info!("Starting Bitcoin");
+    let image = BtcNodeImage::default();
+    images::require(&image).await;
+    let image = RunnableImage::from(image)
+        .with_network("host")
+        .with_container_name("bitcoin");
+    BtcNodeContainer::from_with_host_network(image.start().await)
+}
+
+/// Run blocking RPC call `f` on tokio blocking thread pool.
+pub(crate) async fn run_blocking<T, F>(client: Arc<Client>, f: F) -> T
+where
+    F: FnOnce(&Client) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&client))
+        .await
+        .expect("bitcoin rpc task panicked")
+}
+
+/// Regtest P2WSH(OP_TRUE) address, used to mine blocks when coinbase is not needed.
+pub fn default_mining_address() -> Address {
+    let script = ScriptBuf::builder().push_opcode(OP_TRUE).into_script();
+    Address::p2wsh(&script, Network::Regtest)
+}
+
+/// regtest block reward at `height`: 50 BTC, halved every 150 blocks
+pub fn block_subsidy(height: u64) -> Amount {
+    let halvings = height / 150;
+    if halvings >= 64 {
+        return Amount::ZERO;
+    }
+    Amount::from_sat(50 * 100_000_000 >> halvings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoincore_rpc::{bitcoin::Network, RpcApi};
+
+    #[test]
+    fn test_block_subsidy_halves_every_150_blocks() {
+        assert_eq!(block_subsidy(1), Amount::from_btc(50.0).unwrap());
+        assert_eq!(block_subsidy(149), Amount::from_btc(50.0).unwrap());
+        assert_eq!(block_subsidy(150), Amount::from_btc(25.0).unwrap());
+        assert_eq!(block_subsidy(150 * 64), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_btc_node_args() {
+        let args = BtcNodeArgs::default().to_args();
+        assert!(args.contains(&"-rpcuser=bitcoin".to_string()));
+        assert!(args.contains(&"-txindex".to_string()));
+
+        let args = BtcNodeArgs::default()
+            .with_cookie_auth()
+            .with_rpc_port(18500)
+            .with_zmq_pub("rawtx", 29000)
+            .with_prune(550)
+            .with_arg("-blocksonly");
+        assert_eq!(args.zmq_port("rawtx"), Some(29000));
+        assert_eq!(args.zmq_port("hashblock"), None);
+        assert_eq!(args.clone().without_zmq().zmq_port("rawblock"), None);
+
+        let args = args.to_args();
+        assert!(args.contains(&format!("-rpccookiefile={COOKIE_FILE}")));
+        assert!(args.contains(&"-rpcport=18500".to_string()));
+        assert!(args.contains(&"-zmqpubrawtx=tcp://0.0.0.0:29000".to_string()));
+        assert!(args.contains(&"-prune=550".to_string()));
+        assert!(args.contains(&"-blocksonly".to_string()));
+        assert!(!args
+            .iter()
+            .any(|a| a.starts_with("-rpcuser") || a == "-txindex"));
+    }
+
+    #[tokio::test]
+    async fn test_btc_node_cookie_auth() {
+        let args = BtcNodeArgs::default().with_cookie_auth();
+        let image = RunnableImage::from((BtcNodeImage::default(), args));
+        let node = BtcNodeContainer::from(image.start().await);
+
+        let (user, _) = node.credentials().await;
+        assert_eq!(user, "__cookie__");
+        assert_eq!(node.get_block_count().await, 0);
+        assert_eq!(node.api().await.get_block_count().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_btc_node() {
+        let image: BtcNodeImage = BtcNodeImage::default();
+        let image = RunnableImage::from(image).with_network("host");
+        let node = BtcNodeContainer::from_with_host_network(image.start().await);
+        let api = node.api_with_host_network(None);
+
+        // without this we cannot create new address
+        api.create_wallet("test", None, None, None, None).unwrap();
+
+        let address = api
+            .get_new_address(None, None)
+            .expect("Failed to get new address")
+            .require_network(Network::Regtest)
+            .expect("Should use regtest network");
+
+        // we need to mine 100 blocks to make 1st block spendable
+        api.generate_to_address(101, &address).unwrap();
+        let balance = api.get_balance(None, None).unwrap();
+        assert_eq!(balance.to_btc(), 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_btc_wallet() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let node = BtcNodeContainer::from(image.start().await);
+
+        let alice = node
+            .create_funded_wallet("alice", Amount::from_btc(60.0).unwrap())
+            .await;
+        assert_eq!(alice.balance().await, Amount::from_btc(100.0).unwrap());
+
+        let bob = node.create_wallet("bob").await;
+        let amount = Amount::from_btc(1.0).unwrap();
+        let txid = alice.send(&bob.new_address().await, amount).await;
+
+        node.mine(6).await;
+        node.wait_for_confirmations(&txid, 6, Duration::from_secs(10))
+            .await;
+        assert_eq!(bob.balance().await, amount);
+    }
+
+    #[tokio::test]
+    async fn test_btc_reorg() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let node = BtcNodeContainer::from(image.start().await);
+
+        let old_blocks = node.mine(10).await;
+        let (fork_start, new_blocks) = node.reorg(3, 5).await;
+        assert_eq!(fork_start, old_blocks[7]);
+        assert_eq!(node.get_block_count().await, 12);
+        assert_eq!(node.best_block_hash().await, new_blocks[4]);
+        assert_eq!(node.block_hash(7).await, old_blocks[6]);
+        assert_ne!(node.block_hash(8).await, old_blocks[7]);
+
+        // old chain is shorter, so the node stays on the new one
+        node.reconsider_block(&fork_start).await;
+        assert_eq!(node.best_block_hash().await, new_blocks[4]);
+    }
+
+    #[tokio::test]
+    async fn test_btc_competing_forks() {
+        let (a, b) = start_btc_pair().await;
+
+        a.mine(5).await;
+        a.connect_peer(&b).await;
+        a.wait_for_sync_with(&b, Duration::from_secs(30)).await;
+        a.disconnect_peer(&b).await;
+
+        // build competing chains on top of the common block 5, b's chain is longer
+        a.mine(2).await;
+        let b_blocks = b.mine(4).await;
+        assert_ne!(a.best_block_hash().await, b.best_block_hash().await);
+
+        a.connect_peer(&b).await;
+        let best = a.wait_for_sync_with(&b, Duration::from_secs(30)).await;
+        assert_eq!(best, b_blocks[3]);
+        assert_eq!(a.get_block_count().await, 9);
+    }
+
+    #[tokio::test]
+    async fn test_btc_network_partition() {
+        let network = BtcNetwork::start(3).await;
+        network.connect_all().await;
+        network.node(0).mine(5).await;
+        network.sync_all().await;
+
+        // node 2 is partitioned and lags behind
+        network.disconnect(1, 2).await;
+        network.node(0).mine(3).await;
+        network
+            .node(1)
+            .wait_for_sync_with(network.node(0), Duration::from_secs(30))
+            .await;
+        assert_eq!(network.node(2).get_block_count().await, 5);
+
+        network.connect(2, 0).await;
+        network.sync_all().await;
+        assert_eq!(network.node(2).get_block_count().await, 8);
+    }
+
+    #[tokio::test]
+    async fn test_btc_tx_builder() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let node = BtcNodeContainer::from(image.start().await);
+
+        let alice = node
+            .create_funded_wallet("alice", Amount::from_btc(50.0).unwrap())
+            .await;
+        let bob = node.create_descriptor_wallet("bob").await;
+        let p2wpkh = bob.new_p2wpkh_address().await;
+        let p2tr = bob.new_p2tr_address().await;
+
+        let builder = alice
+            .tx()
+            .pay(&p2wpkh, Amount::from_sat(100_000))
+            .pay(&p2tr, Amount::from_sat(200_000))
+            .op_return(b"request id")
+            .fee_rate(10);
+        let funded = builder.build().await;
+        assert_eq!(funded.change_position, Some(3));
+        assert!(funded.fee >= Amount::from_sat(10 * funded.tx.vsize() as u64));
+
+        let txid = builder.send().await;
+        let tx = node.get_transaction(&txid).await;
+        assert_eq!(tx.output[0].script_pubkey, p2wpkh.script_pubkey());
+        assert_eq!(tx.output[1].value, Amount::from_sat(200_000));
+        assert!(tx.output[2].script_pubkey.is_op_return());
+
+        // PSBT roundtrip
+        let psbt = alice
+            .tx()
+            .pay(&p2tr, Amount::from_sat(300_000))
+            .psbt()
+            .await;
+        let psbt = alice.process_psbt(&psbt).await;
+        let tx = alice.finalize_psbt(&psbt).await;
+        assert_eq!(node.test_mempool_accept(&tx).await, Ok(()));
+        alice.broadcast(&tx).await;
+
+        node.mine(1).await;
+        assert_eq!(bob.balance().await, Amount::from_sat(600_000));
+    }
+
+    #[tokio::test]
+    async fn test_btc_zmq_streams() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let node = BtcNodeContainer::from(image.start().await);
+        let wallet = node
+            .create_funded_wallet("alice", Amount::from_btc(50.0).unwrap())
+            .await;
+
+        let mut blocks = node.block_stream().await;
+        let mut txs = node.tx_stream().await;
+
+        let txid = wallet
+            .send(&wallet.new_address().await, Amount::from_btc(1.0).unwrap())
+            .await;
+        let tx =
+            zmq::next_matching(&mut txs, |tx| tx.txid() == txid, Duration::from_secs(10)).await;
+        assert_eq!(tx.txid(), txid);
+
+        let hash = node.mine(1).await[0];
+        let block = zmq::next_matching(&mut blocks, |_| true, Duration::from_secs(10)).await;
+        assert_eq!(block.block_hash(), hash);
+        assert!(block.txdata.iter().any(|tx| tx.txid() == txid));
+    }
+
+    #[tokio::test]
+    async fn test_btc_mempool() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let node = BtcNodeContainer::from(image.start().await);
+        let wallet = node
+            .create_funded_wallet("alice", Amount::from_btc(50.0).unwrap())
+            .await;
+
+        let txids = node.fill_mempool(&wallet, 4, &[1, 5]).await;
+        assert_eq!(node.mempool_txids().await.len(), 4);
+        // every transaction spends its own confirmed output
+        for txid in &txids {
+            assert!(node.mempool_entry(txid).await.unwrap().depends.is_empty());
+        }
+
+        let stuck = txids[0];
+        node.mine_excluding(&[stuck]).await;
+        assert_eq!(node.mempool_txids().await, vec![stuck]);
+
+        node.prioritise_transaction(&stuck, 10_000).await;
+        let entry = node.mempool_entry(&stuck).await.unwrap();
+        assert!(entry.fees.modified > entry.fees.base);
+
+        let bumped = wallet.bump_fee(&stuck, 20).await;
+        assert!(node.mempool_entry(&stuck).await.is_none());
+        node.mine_including(&[bumped]).await;
+        assert!(node.mempool_txids().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_btc_auto_miner() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let node = BtcNodeContainer::from(image.start().await);
+
+        let miner = node
+            .start_auto_miner(Duration::from_millis(200), &default_mining_address())
+            .await;
+        eventually(Duration::from_secs(10), DEFAULT_POLL_INTERVAL, || async {
+            let height = node.get_block_count().await;
+            if height >= 3 {
+                Ok(())
+            } else {
+                Err(height)
+            }
+        })
+        .await;
+
+        miner.pause();
+        // let a block which is being mined finish
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let height = node.get_block_count().await;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(node.get_block_count().await, height);
+        assert_eq!(miner.blocks_mined(), height);
+        drop(miner);
+
+        let mined = node
+            .mine_until(
+                Duration::from_millis(10),
+                Duration::from_secs(10),
+                || async { node.get_block_count().await >= height + 5 },
+            )
+            .await;
+        assert_eq!(mined, 5);
+    }
+}
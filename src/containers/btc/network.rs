@@ -0,0 +1,131 @@
+//! Several regtest nodes on a shared docker network with controllable peering, to simulate
+//! partitions, lagging nodes and conflicting transactions broadcast to different nodes.
+
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use bitcoincore_rpc::bitcoin::BlockHash;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::RunnableImage;
+use tokio::process::Command;
+
+use super::{BtcNodeContainer, BtcNodeImage};
+use crate::images;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+
+/// [`BtcNetwork::sync_all`] fails if nodes do not agree on the best block within this time
+pub const SYNC_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Regtest nodes on their own docker network. Nodes are not connected to each other on start.
+pub struct BtcNetwork {
+    name: String,
+    nodes: Vec<BtcNodeContainer>,
+}
+
+impl BtcNetwork {
+    /// Start `n` unconnected nodes on a new docker network.
+    pub async fn start(n: usize) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!(
+            "btc-network-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        log::info!("Starting {} Bitcoin nodes on network {}", n, name);
+
+        let image = BtcNodeImage::default();
+        images::require(&image).await;
+
+        let containers = futures::future::join_all((0..n).map(|_| {
+            RunnableImage::from(image.clone())
+                .with_network(name.clone())
+                .start()
+        }))
+        .await;
+
+        let nodes = containers
+            .into_iter()
+            .map(|c| BtcNodeContainer::from_with_network(c, &name))
+            .collect();
+
+        Self { name, nodes }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn nodes(&self) -> &[BtcNodeContainer] {
+        &self.nodes
+    }
+
+    pub fn node(&self, i: usize) -> &BtcNodeContainer {
+        self.nodes
+            .get(i)
+            .unwrap_or_else(|| panic!("no node {i} in network of {} nodes", self.nodes.len()))
+    }
+
+    /// make node `a` connect to node `b`
+    pub async fn connect(&self, a: usize, b: usize) {
+        self.node(a).connect_peer(self.node(b)).await;
+    }
+
+    /// Drop the connection between nodes `a` and `b`, whichever of them opened it.
+    pub async fn disconnect(&self, a: usize, b: usize) {
+        let (a, b) = (self.node(a), self.node(b));
+        let b_address = b.p2p_address().await;
+        if a.peer_addresses().await.contains(&b_address) {
+            a.disconnect_peer(b).await;
+        } else {
+            b.disconnect_peer(a).await;
+        }
+    }
+
+    /// connect every node to the next one, so that all nodes are connected
+    pub async fn connect_all(&self) {
+        for i in 1..self.nodes.len() {
+            self.connect(i - 1, i).await;
+        }
+    }
+
+    /// Wait until all nodes have the same best block and return it.
+    /// Nodes must be connected, directly or through other nodes.
+    pub async fn sync_all(&self) -> BlockHash {
+        eventually(SYNC_TIMEOUT, DEFAULT_POLL_INTERVAL, || async {
+            let hashes =
+                futures::future::join_all(self.nodes.iter().map(|n| n.best_block_hash())).await;
+            match hashes.first() {
+                Some(first) if hashes.iter().all(|h| h == first) => Ok(*first),
+                _ => Err(hashes),
+            }
+        })
+        .await
+    }
+}
+
+/// IP address of container `id` on docker network `network`
+pub(crate) async fn network_ip_address(id: &str, network: &str) -> String {
+    let format = format!("{{{{(index .NetworkSettings.Networks \"{network}\").IPAddress}}}}");
+    let output = Command::new("docker")
+        .args(["inspect", "--format", format.as_str(), id])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .expect("cannot run docker, is it installed?");
+
+    assert!(
+        output.status.success(),
+        "cannot inspect container {}: {}",
+        id,
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+
+    let ip = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert!(
+        !ip.is_empty(),
+        "container {id} is not attached to network {network}"
+    );
+    ip
+}
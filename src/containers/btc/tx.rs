@@ -0,0 +1,260 @@
+//! Raw transaction and PSBT helpers, to craft payments with specific outputs (e.g. OP_RETURN with
+//! a request id, or edge-case payments to a vault address).
+//!
+//! ```ignore
+//! let txid = wallet
+//!     .tx()
+//!     .pay(&vault_address, Amount::from_sat(500_000))
+//!     .op_return(redeem_id.as_bytes())
+//!     .fee_rate(5)
+//!     .send()
+//!     .await;
+//! ```
+
+use bitcoincore_rpc::bitcoin::consensus::encode::{deserialize, serialize_hex};
+use bitcoincore_rpc::bitcoin::{
+    absolute::LockTime, script::PushBytesBuf, transaction::Version, Address, Amount, Network,
+    OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use bitcoincore_rpc::json::{AddressType, FundRawTransactionOptions};
+use bitcoincore_rpc::RpcApi;
+
+use super::{BtcNodeContainer, BtcWallet};
+
+/// Transaction funded by a wallet, but not signed yet.
+#[derive(Debug, Clone)]
+pub struct FundedTx {
+    pub tx: Transaction,
+    pub fee: Amount,
+    /// index of the change output, if the wallet added one
+    pub change_position: Option<usize>,
+}
+
+/// Builds a transaction from outputs in the given order, inputs and change are added by the wallet.
+/// Inputs given with [`TxBuilder::spend`] are used as they are.
+#[derive(Clone)]
+pub struct TxBuilder<'a> {
+    wallet: &'a BtcWallet,
+    inputs: Vec<OutPoint>,
+    outputs: Vec<TxOut>,
+    fee_rate: Option<Amount>,
+    replaceable: bool,
+}
+
+impl<'a> TxBuilder<'a> {
+    /// pay `amount` to `address`
+    pub fn pay(self, address: &Address, amount: Amount) -> Self {
+        self.output(address.script_pubkey(), amount)
+    }
+
+    /// add an output with arbitrary `script_pubkey`
+    pub fn output(mut self, script_pubkey: ScriptBuf, amount: Amount) -> Self {
+        self.outputs.push(TxOut {
+            value: amount,
+            script_pubkey,
+        });
+        self
+    }
+
+    /// spend `outpoint` owned by the wallet, no other inputs are added then
+    pub fn spend(mut self, outpoint: OutPoint) -> Self {
+        self.inputs.push(outpoint);
+        self
+    }
+
+    /// add zero-value OP_RETURN output with `data`, at most 80 bytes are relayed by default
+    pub fn op_return(self, data: &[u8]) -> Self {
+        let data = PushBytesBuf::try_from(data.to_vec()).expect("OP_RETURN data is too long");
+        self.output(ScriptBuf::new_op_return(&data), Amount::ZERO)
+    }
+
+    /// fee rate in sat/vB, wallet default (fallback fee) is used if not set
+    pub fn fee_rate(mut self, sat_per_vb: u64) -> Self {
+        self.fee_rate = Some(Amount::from_sat(sat_per_vb * 1000));
+        self
+    }
+
+    /// signal BIP125 replaceability, the wallet does not do it by default
+    pub fn replaceable(mut self) -> Self {
+        self.replaceable = true;
+        self
+    }
+
+    /// unsigned transaction with outputs and explicit inputs only
+    pub fn unfunded(&self) -> Transaction {
+        let sequence = if self.replaceable {
+            Sequence::ENABLE_RBF_NO_LOCKTIME
+        } else {
+            Sequence::ENABLE_LOCKTIME_NO_RBF
+        };
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: self
+                .inputs
+                .iter()
+                .map(|outpoint| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: ScriptBuf::new(),
+                    sequence,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: self.outputs.clone(),
+        }
+    }
+
+    /// add inputs (unless given explicitly) and change output from the wallet, outputs added so far keep their positions
+    pub async fn build(&self) -> FundedTx {
+        assert!(!self.outputs.is_empty(), "transaction has no outputs");
+
+        let tx = self.unfunded();
+        let options = FundRawTransactionOptions {
+            add_inputs: Some(self.inputs.is_empty()),
+            fee_rate: self.fee_rate,
+            replaceable: Some(self.replaceable),
+            // otherwise bitcoind inserts change at a random index
+            change_position: Some(self.outputs.len() as u32),
+            ..Default::default()
+        };
+        let funded = self
+            .wallet
+            .rpc(move |c| c.fund_raw_transaction(&tx, Some(&options), Some(false)))
+            .await
+            .expect("cannot fund transaction");
+
+        FundedTx {
+            tx: funded.transaction().expect("funded transaction is invalid"),
+            fee: funded.fee,
+            change_position: usize::try_from(funded.change_position).ok(),
+        }
+    }
+
+    /// funded and signed transaction, not broadcast
+    pub async fn build_signed(&self) -> Transaction {
+        let funded = self.build().await;
+        self.wallet.sign(&funded.tx).await
+    }
+
+    /// build, sign and broadcast the transaction
+    pub async fn send(&self) -> Txid {
+        let tx = self.build_signed().await;
+        self.wallet.broadcast(&tx).await
+    }
+
+    /// funded, but not signed PSBT in base64
+    pub async fn psbt(&self) -> String {
+        let funded = self.build().await;
+        self.wallet.to_psbt(&funded.tx).await
+    }
+}
+
+impl BtcWallet {
+    /// start building a transaction paid by this wallet
+    pub fn tx(&self) -> TxBuilder<'_> {
+        TxBuilder {
+            wallet: self,
+            inputs: vec![],
+            outputs: vec![],
+            fee_rate: None,
+            replaceable: false,
+        }
+    }
+
+    /// new address of given type, e.g. [`AddressType::Bech32`] for P2WPKH
+    pub async fn new_address_of_type(&self, address_type: AddressType) -> Address {
+        self.rpc(move |c| c.get_new_address(None, Some(address_type)))
+            .await
+            .expect("Failed to get new address")
+            .require_network(Network::Regtest)
+            .expect("Should use regtest network")
+    }
+
+    pub async fn new_p2wpkh_address(&self) -> Address {
+        self.new_address_of_type(AddressType::Bech32).await
+    }
+
+    /// wallet must be created with [`BtcNodeContainer::create_descriptor_wallet`]
+    pub async fn new_p2tr_address(&self) -> Address {
+        self.new_address_of_type(AddressType::Bech32m).await
+    }
+
+    /// sign all inputs of `tx` owned by this wallet, panics if the transaction is not complete
+    pub async fn sign(&self, tx: &Transaction) -> Transaction {
+        let tx = tx.clone();
+        let signed = self
+            .rpc(move |c| c.sign_raw_transaction_with_wallet(&tx, None, None))
+            .await
+            .expect("cannot sign transaction");
+
+        assert!(
+            signed.complete,
+            "transaction is not completely signed: {:?}",
+            signed.errors
+        );
+        signed.transaction().expect("signed transaction is invalid")
+    }
+
+    /// broadcast `tx` through the node of this wallet
+    pub async fn broadcast(&self, tx: &Transaction) -> Txid {
+        let tx = tx.clone();
+        self.rpc(move |c| c.send_raw_transaction(&tx))
+            .await
+            .expect("cannot broadcast transaction")
+    }
+
+    /// convert unsigned `tx` to a base64 PSBT
+    pub async fn to_psbt(&self, tx: &Transaction) -> String {
+        let hex = serialize_hex(tx);
+        self.rpc(move |c| c.call("converttopsbt", &[serde_json::Value::String(hex)]))
+            .await
+            .expect("cannot convert transaction to PSBT")
+    }
+
+    /// Sign inputs of base64 `psbt` owned by this wallet, returns updated PSBT.
+    pub async fn process_psbt(&self, psbt: &str) -> String {
+        let psbt = psbt.to_string();
+        self.rpc(move |c| c.wallet_process_psbt(&psbt, Some(true), None, None))
+            .await
+            .expect("cannot process PSBT")
+            .psbt
+    }
+
+    /// extract final transaction from a completely signed base64 `psbt`
+    pub async fn finalize_psbt(&self, psbt: &str) -> Transaction {
+        let psbt = psbt.to_string();
+        let finalized = self
+            .rpc(move |c| c.finalize_psbt(&psbt, Some(true)))
+            .await
+            .expect("cannot finalize PSBT");
+
+        assert!(finalized.complete, "PSBT is not completely signed");
+        deserialize(&finalized.hex.expect("finalized PSBT has no transaction"))
+            .expect("finalized transaction is invalid")
+    }
+}
+
+impl BtcNodeContainer {
+    /// Check whether `tx` would be accepted to the mempool, returns the reject reason if not.
+    pub async fn test_mempool_accept(&self, tx: &Transaction) -> Result<(), String> {
+        let tx = tx.clone();
+        let result = self
+            .rpc(move |c| c.test_mempool_accept(&[&tx]))
+            .await
+            .expect("cannot test mempool accept")
+            .remove(0);
+
+        if result.allowed {
+            Ok(())
+        } else {
+            Err(result.reject_reason.unwrap_or_default())
+        }
+    }
+
+    pub async fn get_transaction(&self, txid: &Txid) -> Transaction {
+        let txid = *txid;
+        self.rpc(move |c| c.get_raw_transaction(&txid, None))
+            .await
+            .unwrap_or_else(|e| panic!("cannot get transaction {txid}: {e}"))
+    }
+}
@@ -0,0 +1,117 @@
+//! Async Bitcoin Core wallet API. Blocking RPC calls run on `spawn_blocking`, so they do not
+//! stall the tokio runtime.
+
+use std::sync::Arc;
+
+use bitcoincore_rpc::bitcoin::{Address, Amount, Network, Txid};
+use bitcoincore_rpc::{Client, RpcApi};
+
+use super::{block_subsidy, run_blocking, BtcNodeContainer, COINBASE_MATURITY};
+
+/// A wallet loaded in the Bitcoin node.
+#[derive(Clone)]
+pub struct BtcWallet {
+    name: String,
+    client: Arc<Client>,
+}
+
+impl BtcWallet {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Run blocking RPC call `f` on this wallet's client without stalling the tokio runtime.
+    pub async fn rpc<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&Client) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        run_blocking(self.client.clone(), f).await
+    }
+
+    pub async fn new_address(&self) -> Address {
+        self.rpc(|c| c.get_new_address(None, None))
+            .await
+            .expect("Failed to get new address")
+            .require_network(Network::Regtest)
+            .expect("Should use regtest network")
+    }
+
+    /// send `amount` to `address`, fee is paid on top of it
+    pub async fn send(&self, address: &Address, amount: Amount) -> Txid {
+        let address = address.clone();
+        self.rpc(move |c| c.send_to_address(&address, amount, None, None, None, None, None, None))
+            .await
+            .expect("failed to send to address")
+    }
+
+    /// spendable balance
+    pub async fn balance(&self) -> Amount {
+        self.rpc(|c| c.get_balance(None, None))
+            .await
+            .expect("cannot get wallet balance")
+    }
+}
+
+impl BtcNodeContainer {
+    /// client of an already loaded wallet `name`
+    pub async fn wallet(&self, name: &str) -> BtcWallet {
+        BtcWallet {
+            name: name.to_string(),
+            client: Arc::new(self.client(Some(name)).await),
+        }
+    }
+
+    pub async fn create_wallet(&self, name: &str) -> BtcWallet {
+        log::info!("Creating BTC wallet {}", name);
+        let wallet = name.to_string();
+        self.rpc(move |c| c.create_wallet(&wallet, None, None, None, None))
+            .await
+            .expect("failed to create wallet");
+
+        self.wallet(name).await
+    }
+
+    /// Create a descriptor wallet, it is not the default in Bitcoin Core 22 but is required for
+    /// P2TR addresses.
+    pub async fn create_descriptor_wallet(&self, name: &str) -> BtcWallet {
+        log::info!("Creating BTC descriptor wallet {}", name);
+        let args = [
+            name.into(),
+            false.into(), // disable_private_keys
+            false.into(), // blank
+            "".into(),    // passphrase
+            false.into(), // avoid_reuse
+            true.into(),  // descriptors
+        ];
+        self.rpc(move |c| c.call::<serde_json::Value>("createwallet", &args))
+            .await
+            .expect("failed to create descriptor wallet");
+
+        self.wallet(name).await
+    }
+
+    /// Create wallet `name` and mine blocks to it until it has at least `amount` spendable.
+    /// Coinbase is unlocked after 100 confirmations, so 100 more blocks are mined on top.
+    pub async fn create_funded_wallet(&self, name: &str, amount: Amount) -> BtcWallet {
+        let wallet = self.create_wallet(name).await;
+        let address = wallet.new_address().await;
+
+        let mut height = self.get_block_count().await;
+        let mut blocks = 0;
+        let mut funded = Amount::ZERO;
+        while funded < amount {
+            height += 1;
+            let subsidy = block_subsidy(height);
+            assert!(subsidy > Amount::ZERO, "block subsidy is exhausted");
+            funded += subsidy;
+            blocks += 1;
+        }
+
+        log::info!("Mining {} BTC to wallet {}", funded.to_btc(), name);
+        self.mine_to(blocks, &address).await;
+        self.mine(COINBASE_MATURITY).await;
+
+        wallet
+    }
+}
@@ -0,0 +1,120 @@
+//! Streams of new blocks and transactions from bitcoind ZMQ notifications, to react to an event
+//! the moment it happens instead of polling RPC.
+
+use std::time::Duration;
+
+use bitcoincore_rpc::bitcoin::consensus::encode::deserialize;
+use bitcoincore_rpc::bitcoin::{Block, Transaction};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use zeromq::{Socket, SocketRecv, SubSocket};
+
+use super::BtcNodeContainer;
+
+impl BtcNodeContainer {
+    /// Port of ZMQ `topic` on the host, panics if the topic is not enabled in [`super::BtcNodeArgs`].
+    pub async fn get_zmq_port(&self, topic: &str) -> u16 {
+        let port = self
+            .args()
+            .zmq_port(topic)
+            .unwrap_or_else(|| panic!("ZMQ topic {topic} is not enabled"));
+        if self.host_network {
+            port
+        } else {
+            self.container.get_host_port_ipv4(port).await
+        }
+    }
+
+    /// Blocks connected to the best chain, starting from the next one. Create the stream before
+    /// the action which produces the block, earlier blocks are not replayed.
+    pub async fn block_stream(&self) -> BoxStream<'static, Block> {
+        self.subscribe("rawblock").await
+    }
+
+    /// Transactions accepted to the mempool, and transactions of connected blocks.
+    /// Create the stream before the action which broadcasts the transaction.
+    pub async fn tx_stream(&self) -> BoxStream<'static, Transaction> {
+        self.subscribe("rawtx").await
+    }
+
+    async fn subscribe<T>(&self, topic: &str) -> BoxStream<'static, T>
+    where
+        T: bitcoincore_rpc::bitcoin::consensus::Decodable + Send + 'static,
+    {
+        let endpoint = format!(
+            "tcp://{}:{}",
+            self.get_host(),
+            self.get_zmq_port(topic).await
+        );
+
+        let mut socket = SubSocket::new();
+        socket
+            .connect(&endpoint)
+            .await
+            .unwrap_or_else(|e| panic!("cannot connect to ZMQ {endpoint}: {e}"));
+        socket
+            .subscribe(topic)
+            .await
+            .unwrap_or_else(|e| panic!("cannot subscribe to ZMQ topic {topic}: {e}"));
+        log::debug!("Subscribed to ZMQ {} at {}", topic, endpoint);
+
+        let topic = topic.to_string();
+        futures::stream::unfold(socket, move |mut socket| {
+            let topic = topic.clone();
+            async move {
+                loop {
+                    let message = match socket.recv().await {
+                        Ok(message) => message,
+                        Err(e) => {
+                            log::warn!("ZMQ {} stream is closed: {}", topic, e);
+                            return None;
+                        }
+                    };
+
+                    // frames: topic, body, sequence number
+                    let Some(body) = message.get(1) else {
+                        log::warn!("ZMQ {} message has no body", topic);
+                        continue;
+                    };
+                    match deserialize::<T>(body) {
+                        Ok(item) => return Some((item, socket)),
+                        Err(e) => log::warn!("cannot decode ZMQ {} message: {}", topic, e),
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+/// Wait for the first item of `stream` accepted by `predicate`, panics on timeout.
+///
+/// ```ignore
+/// let mut txs = btc.tx_stream().await;
+/// // ... vault pays the redeem request ...
+/// let payment = next_matching(&mut txs, |tx| pays_to(tx, &address), Duration::from_secs(60)).await;
+/// ```
+pub async fn next_matching<T, P>(
+    stream: &mut BoxStream<'static, T>,
+    predicate: P,
+    timeout: Duration,
+) -> T
+where
+    P: Fn(&T) -> bool,
+{
+    let found = tokio::time::timeout(timeout, async {
+        while let Some(item) = stream.next().await {
+            if predicate(&item) {
+                return Some(item);
+            }
+        }
+        None
+    })
+    .await;
+
+    match found {
+        Ok(Some(item)) => item,
+        Ok(None) => panic!("stream is closed before a matching item is received"),
+        Err(_) => panic!("no matching item is received within {timeout:?}"),
+    }
+}
@@ -8,6 +8,11 @@ use testcontainers::{
     ImageArgs,
 };
 
+use crate::images;
+use crate::logs::ContainerLogs;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use std::time::Duration;
+
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct CosmosImage {}
 
@@ -81,11 +86,41 @@ pub struct ErrorResponse {
 }
 
 pub struct CosmosContainer {
-    pub container: ContainerAsync<CosmosImage>,
-    pub host_network: bool,
+    container: ContainerAsync<CosmosImage>,
+    host_network: bool,
+}
+
+impl ContainerLogs for CosmosContainer {
+    fn container_id(&self) -> &str {
+        self.container.id()
+    }
+
+    fn log_name(&self) -> &str {
+        "cosmos"
+    }
+}
+
+impl Drop for CosmosContainer {
+    fn drop(&mut self) {
+        crate::logs::forget(self.container_id());
+    }
 }
 
 impl CosmosContainer {
+    /// wraps the container started on the host network and starts capturing its logs
+    pub fn from_with_host_network(container: ContainerAsync<CosmosImage>) -> Self {
+        let result = Self {
+            container,
+            host_network: true,
+        };
+        result.logs();
+        result
+    }
+
+    pub fn container(&self) -> &ContainerAsync<CosmosImage> {
+        &self.container
+    }
+
     pub async fn get_port(&self, port: u16) -> u16 {
         if self.host_network {
             port
@@ -123,18 +158,46 @@ impl CosmosContainer {
             ))
         }
     }
+
+    /// poll `denom` balance of `address` until `predicate` accepts it, missing denom counts as 0
+    pub async fn wait_for_bank_balance(
+        &self,
+        address: &str,
+        denom: &str,
+        predicate: impl Fn(Decimal) -> bool,
+        timeout: Duration,
+    ) -> Decimal {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let balances = self
+                .get_bank_balances_by_address(address)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let amount = balances
+                .balances
+                .iter()
+                .find(|b| b.denom == denom)
+                .map(|b| b.amount)
+                .unwrap_or_default();
+
+            if predicate(amount) {
+                Ok(amount)
+            } else {
+                Err(format!("{address} has {amount} {denom}"))
+            }
+        })
+        .await
+    }
 }
 
 pub async fn start_cosmos() -> CosmosContainer {
     log::info!("Starting Cosmos");
     let image = CosmosImage::default();
+    images::require(&image).await;
     let image = RunnableImage::from(image)
         .with_network("host")
         .with_container_name("cosmos");
-    CosmosContainer {
-        container: image.start().await,
-        host_network: true,
-    }
+    CosmosContainer::from_with_host_network(image.start().await)
 }
 
 #[cfg(test)]
@@ -157,10 +220,7 @@ mod cosmos_tests {
             .with_network("host")
             .with_container_name("cosmos");
         let container = image.start().await;
-        let node = CosmosContainer {
-            container,
-            host_network: true,
-        };
+        let node = CosmosContainer::from_with_host_network(container);
 
         const ALICE_COSMOS_ADDRESS: &str = "cosmos1xh2jvz9ecty8qdctlgscmys2dr5gz729k0l7x4";
 
@@ -0,0 +1,362 @@
+//! Electrs (Esplora flavour) indexing a regtest bitcoind, and a typed client of its Esplora HTTP
+//! API. interbtc-clients use it in light-client mode (`--electrs-url`).
+//!
+//! Only bitcoind is supported as the backend, btcd is out of scope: the vault still needs the
+//! bitcoind wallet RPC (`createwallet`, `fundrawtransaction`, ...) next to electrs, and btcd has
+//! no wallet, so there would be nothing to test with it.
+
+use std::time::Duration;
+
+use bitcoincore_rpc::bitcoin::consensus::encode::serialize_hex;
+use bitcoincore_rpc::bitcoin::{Address, BlockHash, Transaction, Txid};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use testcontainers::core::{Image, RunnableImage, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, ImageArgs};
+
+use crate::containers::btc::BtcNodeContainer;
+use crate::images;
+use crate::logs::ContainerLogs;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+
+/// Esplora HTTP API port in the container
+pub const HTTP_PORT: u16 = 3002;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ElectrsImage {
+    image: String,
+    tag: String,
+}
+
+impl Default for ElectrsImage {
+    fn default() -> Self {
+        Self {
+            image: "interlayhq/electrs".to_string(),
+            tag: "v0.5.10".to_string(),
+        }
+    }
+}
+
+impl ElectrsImage {
+    pub fn with_image(mut self, image: String) -> Self {
+        self.image = image;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: String) -> Self {
+        self.tag = tag;
+        self
+    }
+}
+
+impl Image for ElectrsImage {
+    type Args = ElectrsArgs;
+
+    fn name(&self) -> String {
+        self.image.clone()
+    }
+
+    fn tag(&self) -> String {
+        self.tag.clone()
+    }
+
+    fn ready_conditions(&self) -> Vec<WaitFor> {
+        vec![WaitFor::message_on_stderr("REST server running on")]
+    }
+
+    fn expose_ports(&self) -> Vec<u16> {
+        vec![HTTP_PORT]
+    }
+}
+
+/// bitcoind connection of electrs
+#[derive(Debug, Clone)]
+pub struct ElectrsArgs {
+    /// `ip:port` of bitcoind RPC, reachable from the electrs container
+    pub daemon_rpc_addr: String,
+    pub rpc_user: String,
+    pub rpc_password: String,
+}
+
+impl Default for ElectrsArgs {
+    fn default() -> Self {
+        Self {
+            daemon_rpc_addr: "127.0.0.1:18443".to_string(),
+            rpc_user: "bitcoin".to_string(),
+            rpc_password: "bitcoin".to_string(),
+        }
+    }
+}
+
+impl ImageArgs for ElectrsArgs {
+    fn into_iterator(self) -> Box<dyn Iterator<Item = String>> {
+        let args = vec![
+            "electrs".to_string(),
+            "--network=regtest".to_string(),
+            "--jsonrpc-import".to_string(),
+            "--index-unspendables".to_string(),
+            "--db-dir=/tmp/electrs".to_string(),
+            format!("--daemon-rpc-addr={}", self.daemon_rpc_addr),
+            format!("--cookie={}:{}", self.rpc_user, self.rpc_password),
+            format!("--http-addr=0.0.0.0:{HTTP_PORT}"),
+            "-vv".to_string(),
+        ];
+        Box::new(args.into_iter())
+    }
+}
+
+pub struct ElectrsContainer {
+    container: ContainerAsync<ElectrsImage>,
+    host_network: bool,
+}
+
+impl ContainerLogs for ElectrsContainer {
+    fn container_id(&self) -> &str {
+        self.container.id()
+    }
+
+    fn log_name(&self) -> &str {
+        "electrs"
+    }
+}
+
+impl Drop for ElectrsContainer {
+    fn drop(&mut self) {
+        crate::logs::forget(self.container_id());
+    }
+}
+
+impl ElectrsContainer {
+    /// wraps the container and starts capturing its logs
+    pub fn from(container: ContainerAsync<ElectrsImage>, host_network: bool) -> Self {
+        let result = Self {
+            container,
+            host_network,
+        };
+        result.logs();
+        result
+    }
+
+    pub async fn get_http_port(&self) -> u16 {
+        if self.host_network {
+            HTTP_PORT
+        } else {
+            self.container.get_host_port_ipv4(HTTP_PORT).await
+        }
+    }
+
+    /// Esplora API url on the host, e.g. for `--electrs-url` of a host network vault
+    pub async fn get_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.get_http_port().await)
+    }
+
+    pub async fn esplora(&self) -> EsploraClient {
+        EsploraClient::new(&self.get_url().await)
+    }
+
+    /// Wait until electrs indexes the best block of `btc`.
+    pub async fn wait_for_sync(&self, btc: &BtcNodeContainer, timeout: Duration) -> BlockHash {
+        let esplora = self.esplora().await;
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let best = btc.best_block_hash().await;
+            let tip = esplora.tip_hash().await.map_err(|e| e.to_string())?;
+            if tip == best {
+                Ok(tip)
+            } else {
+                Err(format!("electrs tip is {tip}, bitcoind tip is {best}"))
+            }
+        })
+        .await
+    }
+}
+
+/// Start electrs indexing `btc`, on the same network as `btc`.
+pub async fn start_electrs(btc: &BtcNodeContainer) -> ElectrsContainer {
+    log::info!("Starting Electrs");
+    let image = ElectrsImage::default();
+    images::require(&image).await;
+
+    let (rpc_user, rpc_password) = btc.credentials().await;
+    let args = ElectrsArgs {
+        daemon_rpc_addr: btc.container_rpc_address().await,
+        rpc_user,
+        rpc_password,
+    };
+
+    let mut image = RunnableImage::from((image, args));
+    if btc.is_host_network() {
+        image = image.with_network("host");
+    } else if let Some(network) = btc.network() {
+        image = image.with_network(network);
+    }
+
+    ElectrsContainer::from(image.start().await, btc.is_host_network())
+}
+
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct TxStatus {
+    pub confirmed: bool,
+    pub block_height: Option<u64>,
+    pub block_hash: Option<BlockHash>,
+}
+
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct TxOutput {
+    /// hex
+    pub scriptpubkey: String,
+    pub scriptpubkey_address: Option<String>,
+    pub value: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct EsploraTx {
+    pub txid: Txid,
+    pub vout: Vec<TxOutput>,
+    pub fee: u64,
+    pub status: TxStatus,
+}
+
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Utxo {
+    pub txid: Txid,
+    pub vout: u32,
+    pub value: u64,
+    pub status: TxStatus,
+}
+
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AddressTxStats {
+    pub tx_count: u64,
+    pub funded_txo_count: u64,
+    pub funded_txo_sum: u64,
+    pub spent_txo_count: u64,
+    pub spent_txo_sum: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AddressStats {
+    pub address: String,
+    pub chain_stats: AddressTxStats,
+    pub mempool_stats: AddressTxStats,
+}
+
+impl AddressStats {
+    /// confirmed balance in satoshi
+    pub fn confirmed_balance(&self) -> u64 {
+        self.chain_stats.funded_txo_sum - self.chain_stats.spent_txo_sum
+    }
+}
+
+/// Client of the Esplora HTTP API.
+#[derive(Debug, Clone)]
+pub struct EsploraClient {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl EsploraClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn get_text(&self, path: &str) -> anyhow::Result<String> {
+        let response = self.http.get(format!("{}{path}", self.url)).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if status.is_success() {
+            Ok(body)
+        } else {
+            Err(anyhow::anyhow!("GET {path} failed with {status}: {body}"))
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        Ok(serde_json::from_str(&self.get_text(path).await?)?)
+    }
+
+    pub async fn tip_height(&self) -> anyhow::Result<u64> {
+        Ok(self.get_text("/blocks/tip/height").await?.trim().parse()?)
+    }
+
+    pub async fn tip_hash(&self) -> anyhow::Result<BlockHash> {
+        Ok(self.get_text("/blocks/tip/hash").await?.trim().parse()?)
+    }
+
+    pub async fn tx(&self, txid: &Txid) -> anyhow::Result<EsploraTx> {
+        self.get_json(&format!("/tx/{txid}")).await
+    }
+
+    pub async fn tx_status(&self, txid: &Txid) -> anyhow::Result<TxStatus> {
+        self.get_json(&format!("/tx/{txid}/status")).await
+    }
+
+    pub async fn address_stats(&self, address: &Address) -> anyhow::Result<AddressStats> {
+        self.get_json(&format!("/address/{address}")).await
+    }
+
+    /// newest first, mempool transactions included
+    pub async fn address_txs(&self, address: &Address) -> anyhow::Result<Vec<EsploraTx>> {
+        self.get_json(&format!("/address/{address}/txs")).await
+    }
+
+    pub async fn address_utxos(&self, address: &Address) -> anyhow::Result<Vec<Utxo>> {
+        self.get_json(&format!("/address/{address}/utxo")).await
+    }
+
+    pub async fn broadcast(&self, tx: &Transaction) -> anyhow::Result<Txid> {
+        let response = self
+            .http
+            .post(format!("{}/tx", self.url))
+            .body(serialize_hex(tx))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if status.is_success() {
+            Ok(body.trim().parse()?)
+        } else {
+            Err(anyhow::anyhow!("POST /tx failed with {status}: {body}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoincore_rpc::bitcoin::Amount;
+
+    use super::*;
+    use crate::containers::btc::BtcNodeImage;
+
+    #[tokio::test]
+    async fn test_electrs_indexes_address_history() {
+        let image: RunnableImage<BtcNodeImage> = BtcNodeImage::default().into();
+        let btc = BtcNodeContainer::from(image.start().await);
+        let wallet = btc
+            .create_funded_wallet("alice", Amount::from_btc(50.0).unwrap())
+            .await;
+
+        let electrs = start_electrs(&btc).await;
+        let address = wallet.new_address().await;
+        let txid = wallet.send(&address, Amount::from_sat(100_000)).await;
+        btc.mine(1).await;
+        electrs.wait_for_sync(&btc, Duration::from_secs(60)).await;
+
+        let esplora = electrs.esplora().await;
+        assert_eq!(
+            esplora.tip_height().await.unwrap(),
+            btc.get_block_count().await
+        );
+
+        let stats = esplora.address_stats(&address).await.unwrap();
+        assert_eq!(stats.confirmed_balance(), 100_000);
+
+        let txs = esplora.address_txs(&address).await.unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].txid, txid);
+        assert!(txs[0].status.confirmed);
+    }
+}
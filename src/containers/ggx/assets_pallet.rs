@@ -1,7 +1,9 @@
 use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
 use crate::metadata;
 use crate::metadata::ggx::runtime_types::pallet_assets::types::AssetAccount;
+use crate::wait::{wait_until, DEFAULT_POLL_INTERVAL};
 use async_trait::async_trait;
+use std::time::Duration;
 use subxt::utils::{AccountId32, MultiAddress};
 use subxt_signer::sr25519::{dev, Keypair};
 
@@ -57,6 +59,31 @@ pub trait AssetsPallet: SubstrateApi {
         self.send_tx_and_wait_until_finalized(dev::alice(), tx)
             .await;
     }
+
+    /// poll balance of `owner` until `predicate` accepts it, missing account counts as 0
+    async fn wait_for_asset_balance<P>(
+        &self,
+        owner: Keypair,
+        asset_id: u32,
+        predicate: P,
+        timeout: Duration,
+    ) -> u128
+    where
+        P: Fn(u128) -> bool + Send + Sync,
+    {
+        wait_until(
+            timeout,
+            DEFAULT_POLL_INTERVAL,
+            || async {
+                self.asset_get_balance(owner.clone(), asset_id)
+                    .await
+                    .map(|a| a.balance)
+                    .unwrap_or_default()
+            },
+            |balance| predicate(*balance),
+        )
+        .await
+    }
 }
 
 #[async_trait]
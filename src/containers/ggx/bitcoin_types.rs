@@ -0,0 +1,254 @@
+//! Conversions from rust-bitcoin types (as returned by Bitcoin Core RPC) to the runtime types of
+//! btc_relay/issue/redeem calls.
+
+use bitcoincore_rpc::bitcoin::consensus::encode::{deserialize, serialize};
+use bitcoincore_rpc::bitcoin::hashes::Hash;
+use bitcoincore_rpc::bitcoin::{
+    absolute, block::Header, pow::Target, Address, BlockHash, MerkleBlock, Network,
+    Script as BtcScript, Transaction as BtcTransaction, Txid,
+};
+use bitcoincore_rpc::RpcApi;
+use subxt::utils::{H160, H256};
+
+use crate::containers::btc::BtcNodeContainer;
+use crate::metadata::ggx::runtime_types::bitcoin::merkle::{MerkleProof, PartialTransactionProof};
+use crate::metadata::ggx::runtime_types::bitcoin::script::Script;
+use crate::metadata::ggx::runtime_types::bitcoin::types::{
+    BlockHeader, FullTransactionProof, H256Le, LockTime, Transaction, TransactionInput,
+    TransactionInputSource, TransactionOutput,
+};
+use crate::metadata::ggx::runtime_types::primitive_types::U256;
+
+/// H256Le keeps bytes in the internal (serialization) order, which is reversed to the hex
+/// shown by Bitcoin Core.
+pub fn to_h256le<H: Hash<Bytes = [u8; 32]>>(hash: &H) -> H256Le {
+    H256Le {
+        content: hash.to_byte_array(),
+    }
+}
+
+pub fn to_block_hash(hash: &H256Le) -> BlockHash {
+    BlockHash::from_byte_array(hash.content)
+}
+
+pub fn to_u256(target: Target) -> U256 {
+    let bytes = target.to_le_bytes();
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+    }
+    U256(limbs)
+}
+
+pub fn to_block_header(header: &Header) -> BlockHeader {
+    BlockHeader {
+        merkle_root: to_h256le(&header.merkle_root),
+        target: to_u256(header.target()),
+        timestamp: header.time,
+        version: header.version.to_consensus(),
+        hash: to_h256le(&header.block_hash()),
+        hash_prev_block: to_h256le(&header.prev_blockhash),
+        nonce: header.nonce,
+    }
+}
+
+/// Coinbase inputs are converted to `Coinbase(None)` with the whole script, so that the
+/// transaction serializes to the same bytes (and txid).
+pub fn to_transaction(tx: &BtcTransaction) -> Transaction {
+    let inputs = tx
+        .input
+        .iter()
+        .map(|input| {
+            let source = if input.previous_output.is_null() {
+                TransactionInputSource::Coinbase(None)
+            } else {
+                TransactionInputSource::FromOutput(
+                    to_h256le(&input.previous_output.txid),
+                    input.previous_output.vout,
+                )
+            };
+            TransactionInput {
+                source,
+                script: input.script_sig.to_bytes(),
+                sequence: input.sequence.0,
+                witness: input.witness.to_vec(),
+            }
+        })
+        .collect();
+
+    let outputs = tx
+        .output
+        .iter()
+        .map(|output| TransactionOutput {
+            value: output.value.to_sat() as i64,
+            script: Script {
+                bytes: output.script_pubkey.to_bytes(),
+            },
+        })
+        .collect();
+
+    let lock_at = match tx.lock_time {
+        absolute::LockTime::Blocks(height) => LockTime::BlockHeight(height.to_consensus_u32()),
+        absolute::LockTime::Seconds(time) => LockTime::Time(time.to_consensus_u32()),
+    };
+
+    Transaction {
+        version: tx.version.0,
+        inputs,
+        outputs,
+        lock_at,
+    }
+}
+
+/// `merkle_block` is a proof from `gettxoutproof`
+pub fn to_merkle_proof(merkle_block: &MerkleBlock) -> MerkleProof {
+    let tree = &merkle_block.txn;
+    MerkleProof {
+        block_header: to_block_header(&merkle_block.header),
+        flag_bits: tree.bits().clone(),
+        transactions_count: tree.num_transactions(),
+        hashes: tree.hashes().iter().map(to_h256le).collect(),
+    }
+}
+
+/// proof that `tx` is included into the block of `merkle_block`
+pub fn to_partial_proof(
+    tx: &BtcTransaction,
+    merkle_block: &MerkleBlock,
+) -> PartialTransactionProof {
+    PartialTransactionProof {
+        transaction: to_transaction(tx),
+        tx_encoded_len: serialize(tx).len() as u32,
+        merkle_proof: to_merkle_proof(merkle_block),
+    }
+}
+
+/// regtest address of an interbtc address, e.g. `vault_address` of `RequestIssue`
+pub fn to_btc_address(address: &bitcoin::Address) -> Address {
+    let script = address.to_script_pub_key();
+    Address::from_script(BtcScript::from_bytes(script.as_bytes()), Network::Regtest)
+        .expect("unsupported btc address")
+}
+
+/// interbtc address of regtest `address`, e.g. for `request_redeem`.
+/// Only P2PKH, P2SH, P2WPKH and P2WSH addresses are supported by the runtime.
+pub fn to_interbtc_address(address: &Address) -> bitcoin::Address {
+    let script = address.script_pubkey();
+    let bytes = script.as_bytes();
+    if script.is_p2pkh() {
+        bitcoin::Address::P2PKH(H160::from_slice(&bytes[3..23]))
+    } else if script.is_p2sh() {
+        bitcoin::Address::P2SH(H160::from_slice(&bytes[2..22]))
+    } else if script.is_p2wpkh() {
+        bitcoin::Address::P2WPKHv0(H160::from_slice(&bytes[2..22]))
+    } else if script.is_p2wsh() {
+        bitcoin::Address::P2WSHv0(H256::from_slice(&bytes[2..34]))
+    } else {
+        panic!("address {address} is not supported by interbtc");
+    }
+}
+
+/// Proof of inclusion of confirmed `txid` for `execute_*` calls of issue/redeem/replace.
+pub async fn full_transaction_proof(btc: &BtcNodeContainer, txid: &Txid) -> FullTransactionProof {
+    let txid = *txid;
+    let (tx, coinbase, tx_proof, coinbase_proof) = btc
+        .rpc(move |c| -> bitcoincore_rpc::Result<_> {
+            let info = c.get_raw_transaction_info(&txid, None)?;
+            let block_hash = info
+                .blockhash
+                .expect("transaction is not included into a block");
+            let tx = info.transaction()?;
+            let block = c.get_block(&block_hash)?;
+            let coinbase = block.txdata[0].clone();
+            let tx_proof = c.get_tx_out_proof(&[txid], Some(&block_hash))?;
+            let coinbase_proof = c.get_tx_out_proof(&[coinbase.txid()], Some(&block_hash))?;
+            Ok((tx, coinbase, tx_proof, coinbase_proof))
+        })
+        .await
+        .unwrap_or_else(|e| panic!("cannot get proof of {txid}: {e}"));
+
+    let tx_proof: MerkleBlock = deserialize(&tx_proof).expect("invalid merkle proof");
+    let coinbase_proof: MerkleBlock = deserialize(&coinbase_proof).expect("invalid merkle proof");
+
+    FullTransactionProof {
+        user_tx_proof: to_partial_proof(&tx, &tx_proof),
+        coinbase_proof: to_partial_proof(&coinbase, &coinbase_proof),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoincore_rpc::bitcoin::{block::Version, CompactTarget, TxMerkleNode};
+
+    use super::*;
+
+    #[test]
+    fn test_h256le_keeps_internal_byte_order() {
+        let hash: BlockHash = "0000000000000000000000000000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let h = to_h256le(&hash);
+        assert_eq!(h.content[0], 1);
+        assert_eq!(to_block_hash(&h), hash);
+    }
+
+    #[test]
+    fn test_regtest_target_to_u256() {
+        // regtest pow limit: 0x7fffff << 232
+        let header = Header {
+            version: Version::ONE,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 0,
+            bits: CompactTarget::from_consensus(0x207fffff),
+            nonce: 0,
+        };
+        let converted = to_block_header(&header);
+        assert_eq!(converted.target.0, [0, 0, 0, 0x7fffff << 40]);
+        assert_eq!(converted.version, 1);
+    }
+
+    #[test]
+    fn test_interbtc_address_roundtrip() {
+        let mut addresses: Vec<Address> = [
+            "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+            "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+            "2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc",
+        ]
+        .iter()
+        .map(|a| {
+            a.parse::<Address<_>>()
+                .unwrap()
+                .require_network(Network::Regtest)
+                .unwrap()
+        })
+        .collect();
+        addresses.push(Address::p2wsh(
+            BtcScript::from_bytes(&[0x51]),
+            Network::Regtest,
+        ));
+
+        for address in addresses {
+            assert_eq!(to_btc_address(&to_interbtc_address(&address)), address);
+        }
+    }
+
+    #[test]
+    fn test_coinbase_input() {
+        // genesis coinbase
+        let raw = hex::decode("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000").unwrap();
+        let tx: BtcTransaction = deserialize(&raw).unwrap();
+
+        let converted = to_transaction(&tx);
+        assert!(matches!(
+            converted.inputs[0].source,
+            TransactionInputSource::Coinbase(None)
+        ));
+        assert_eq!(
+            converted.inputs[0].script,
+            tx.input[0].script_sig.to_bytes()
+        );
+        assert_eq!(converted.outputs[0].value, 50 * 100_000_000);
+        assert!(matches!(converted.lock_at, LockTime::BlockHeight(0)));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::containers::btc::BtcNodeContainer;
+use crate::containers::ggx::bitcoin_types::{to_block_hash, to_block_header, to_h256le};
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::bitcoin::types::BlockChain;
+use crate::metadata::ggx::runtime_types::btc_relay::types::RichBlockHeader;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::block::Header;
+use bitcoincore_rpc::bitcoin::BlockHash;
+use std::time::Duration;
+use subxt_signer::sr25519::Keypair;
+
+/// fork bound used by [`BtcRelayPallet::btc_relay_relay_blocks`], same as the vault uses
+pub const DEFAULT_FORK_BOUND: u32 = 10;
+
+#[async_trait]
+pub trait BtcRelayPallet: SubstrateApi {
+    /// `None` if the relay is not initialized yet
+    async fn btc_relay_best_block(&self) -> Option<BlockHash> {
+        let query = metadata::ggx::storage().btc_relay().best_block();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get btc best block")
+            .map(|hash| to_block_hash(&hash))
+    }
+
+    async fn btc_relay_best_block_height(&self) -> u32 {
+        let query = metadata::ggx::storage().btc_relay().best_block_height();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get btc best block height")
+    }
+
+    /// BTC confirmations required by the relay to accept a transaction proof
+    async fn btc_relay_stable_confirmations(&self) -> u32 {
+        let query = metadata::ggx::storage()
+            .btc_relay()
+            .stable_bitcoin_confirmations();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get stable bitcoin confirmations")
+    }
+
+    async fn btc_relay_block_header(&self, hash: &BlockHash) -> Option<RichBlockHeader<u32>> {
+        let query = metadata::ggx::storage()
+            .btc_relay()
+            .block_headers(to_h256le(hash));
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get btc block header")
+    }
+
+    /// main chain and all forks known to the relay
+    async fn btc_relay_chains(&self) -> Vec<BlockChain> {
+        let query = metadata::ggx::storage().btc_relay().chains_index_root();
+
+        let mut it = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .iter(query, 100)
+            .await
+            .expect("cannot iter");
+
+        let mut chains = vec![];
+        while let Ok(Some(v)) = it.next().await {
+            chains.push(v.1);
+        }
+        chains
+    }
+
+    async fn btc_relay_initialize(&self, relayer: Keypair, header: &Header, height: u32) {
+        log::info!(
+            "GGX: Initializing BTC relay at height {} with {}",
+            height,
+            header.block_hash()
+        );
+        let tx = metadata::ggx::tx()
+            .btc_relay()
+            .initialize(to_block_header(header), height);
+        self.send_tx_and_wait_until_finalized(relayer, tx).await;
+    }
+
+    async fn btc_relay_store_block_header(
+        &self,
+        relayer: Keypair,
+        header: &Header,
+        fork_bound: u32,
+    ) {
+        log::debug!("GGX: Storing BTC block header {}", header.block_hash());
+        let tx = metadata::ggx::tx()
+            .btc_relay()
+            .store_block_header(to_block_header(header), fork_bound);
+        self.send_tx_and_wait_until_finalized(relayer, tx).await;
+    }
+
+    /// Act as a relayer: initialize the relay with the best block of `btc` if needed,
+    /// then submit headers of the main chain of `btc` which the relay does not know yet,
+    /// starting from the last common block (so that reorgs of `btc` are relayed as forks).
+    async fn btc_relay_relay_blocks(&self, relayer: Keypair, btc: &BtcNodeContainer) {
+        let btc_height = btc.get_block_count().await as u32;
+
+        let mut common_height = if self.btc_relay_best_block().await.is_none() {
+            let header = btc
+                .block_header(btc.block_hash(btc_height as u64).await)
+                .await;
+            self.btc_relay_initialize(relayer.clone(), &header, btc_height)
+                .await;
+            btc_height
+        } else {
+            self.btc_relay_best_block_height().await.min(btc_height)
+        };
+
+        while self
+            .btc_relay_block_header(&btc.block_hash(common_height as u64).await)
+            .await
+            .is_none()
+        {
+            assert!(common_height > 0, "relay has no common block with BTC node");
+            common_height -= 1;
+        }
+
+        for height in common_height + 1..=btc_height {
+            let hash = btc.block_hash(height as u64).await;
+            // already stored as a fork block
+            if self.btc_relay_block_header(&hash).await.is_some() {
+                continue;
+            }
+            let header = btc.block_header(hash).await;
+            self.btc_relay_store_block_header(relayer.clone(), &header, DEFAULT_FORK_BOUND)
+                .await;
+        }
+    }
+
+    /// wait until the best block of the relay is the best block of `btc`
+    async fn btc_relay_wait_for_sync_with(
+        &self,
+        btc: &BtcNodeContainer,
+        timeout: Duration,
+    ) -> BlockHash {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let btc_best = btc.best_block_hash().await;
+            match self.btc_relay_best_block().await {
+                Some(best) if best == btc_best => Ok(best),
+                best => Err(format!(
+                    "relay best block: {best:?}, BTC best block: {btc_best}"
+                )),
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl BtcRelayPallet for GgxNodeContainer {}
@@ -0,0 +1,168 @@
+use crate::containers::ggx::vault_registry_pallet::FIXED_ONE;
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{
+    CurrencyId, VaultCurrencyPair, VaultId,
+};
+use crate::metadata::ggx::runtime_types::sp_arithmetic::fixed_point::FixedU128;
+use async_trait::async_trait;
+use subxt::storage::address::Yes;
+use subxt::storage::StorageAddress;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_signer::sr25519::Keypair;
+
+type RuntimeCall = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+type FeeCall = metadata::ggx::runtime_types::fee::pallet::Call;
+
+/// `rate` of `amount`, rounded to the nearest
+pub fn fee_amount(amount: u128, rate: &FixedU128) -> u128 {
+    let scaled = amount.checked_mul(rate.0).expect("amount is too large");
+    (scaled + FIXED_ONE / 2) / FIXED_ONE
+}
+
+/// value of a fee parameter, zero if not set
+async fn fetch_rate<Address>(api: &OnlineClient<PolkadotConfig>, query: Address) -> FixedU128
+where
+    Address: StorageAddress<Target = FixedU128, IsFetchable = Yes, IsDefaultable = Yes>,
+{
+    api.storage()
+        .at_latest()
+        .await
+        .expect("cannot get storage at latest")
+        .fetch_or_default(&query)
+        .await
+        .expect("cannot get fee")
+}
+
+#[async_trait]
+pub trait FeePallet: SubstrateApi {
+    /// share of issued tokens paid by the requester
+    async fn fee_issue_fee(&self) -> FixedU128 {
+        fetch_rate(self.api(), metadata::ggx::storage().fee().issue_fee()).await
+    }
+
+    /// share of redeemed tokens paid by the redeemer
+    async fn fee_redeem_fee(&self) -> FixedU128 {
+        fetch_rate(self.api(), metadata::ggx::storage().fee().redeem_fee()).await
+    }
+
+    async fn fee_premium_redeem_fee(&self) -> FixedU128 {
+        fetch_rate(
+            self.api(),
+            metadata::ggx::storage().fee().premium_redeem_fee(),
+        )
+        .await
+    }
+
+    async fn fee_punishment_fee(&self) -> FixedU128 {
+        fetch_rate(self.api(), metadata::ggx::storage().fee().punishment_fee()).await
+    }
+
+    async fn fee_issue_griefing_collateral(&self) -> FixedU128 {
+        fetch_rate(
+            self.api(),
+            metadata::ggx::storage().fee().issue_griefing_collateral(),
+        )
+        .await
+    }
+
+    async fn fee_replace_griefing_collateral(&self) -> FixedU128 {
+        fetch_rate(
+            self.api(),
+            metadata::ggx::storage().fee().replace_griefing_collateral(),
+        )
+        .await
+    }
+
+    /// share of rewards the vault keeps before distributing to its nominators
+    async fn fee_commission(&self, vault_id: VaultId<AccountId32, CurrencyId>) -> FixedU128 {
+        fetch_rate(
+            self.api(),
+            metadata::ggx::storage().fee().commission(vault_id),
+        )
+        .await
+    }
+
+    async fn fee_set_issue_fee(&self, fee: FixedU128) {
+        log::info!("GGX: Setting issue fee to {:?}", fee);
+        self.sudo(RuntimeCall::Fee(FeeCall::set_issue_fee { fee }))
+            .await;
+    }
+
+    async fn fee_set_redeem_fee(&self, fee: FixedU128) {
+        log::info!("GGX: Setting redeem fee to {:?}", fee);
+        self.sudo(RuntimeCall::Fee(FeeCall::set_redeem_fee { fee }))
+            .await;
+    }
+
+    async fn fee_set_premium_redeem_fee(&self, fee: FixedU128) {
+        log::info!("GGX: Setting premium redeem fee to {:?}", fee);
+        self.sudo(RuntimeCall::Fee(FeeCall::set_premium_redeem_fee { fee }))
+            .await;
+    }
+
+    async fn fee_set_punishment_fee(&self, fee: FixedU128) {
+        log::info!("GGX: Setting punishment fee to {:?}", fee);
+        self.sudo(RuntimeCall::Fee(FeeCall::set_punishment_fee { fee }))
+            .await;
+    }
+
+    async fn fee_set_issue_griefing_collateral(&self, griefing_collateral: FixedU128) {
+        log::info!(
+            "GGX: Setting issue griefing collateral to {:?}",
+            griefing_collateral
+        );
+        self.sudo(RuntimeCall::Fee(FeeCall::set_issue_griefing_collateral {
+            griefing_collateral,
+        }))
+        .await;
+    }
+
+    async fn fee_set_replace_griefing_collateral(&self, griefing_collateral: FixedU128) {
+        log::info!(
+            "GGX: Setting replace griefing collateral to {:?}",
+            griefing_collateral
+        );
+        self.sudo(RuntimeCall::Fee(FeeCall::set_replace_griefing_collateral {
+            griefing_collateral,
+        }))
+        .await;
+    }
+
+    /// Set the commission of the vault of `vault` with `currency_pair`, signed by the vault.
+    async fn fee_set_commission(
+        &self,
+        vault: Keypair,
+        currency_pair: VaultCurrencyPair<CurrencyId>,
+        commission: FixedU128,
+    ) {
+        log::info!(
+            "GGX: Setting commission of {:?} to {:?}",
+            currency_pair,
+            commission
+        );
+        let tx = metadata::ggx::tx()
+            .fee()
+            .set_commission(currency_pair, commission);
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+}
+
+#[async_trait]
+impl FeePallet for GgxNodeContainer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_amount() {
+        // 0.5%
+        let rate = FixedU128(FIXED_ONE / 200);
+        assert_eq!(fee_amount(500_000, &rate), 2_500);
+        assert_eq!(fee_amount(301, &rate), 2);
+        assert_eq!(fee_amount(299, &rate), 1);
+        assert_eq!(fee_amount(500_000, &FixedU128(0)), 0);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::containers::btc::{BtcNodeContainer, BtcWallet};
+use crate::containers::ggx::bitcoin_types::{full_transaction_proof, to_btc_address};
+use crate::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata::ggx::issue::events::{ExecuteIssue, RequestIssue};
+use crate::metadata::ggx::runtime_types::interbtc_primitives::issue::{
+    IssueRequest, IssueRequestStatus,
+};
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{
+    CurrencyId, TokenSymbol, VaultCurrencyPair, VaultId,
+};
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use crate::{handle_tx_error, metadata, module_error};
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::{Amount, Txid};
+use std::time::Duration;
+use subxt::utils::{AccountId32, H256};
+use subxt_signer::sr25519::Keypair;
+
+/// BTC blocks and GGX relay must catch up, and the issue must be executed, within this time in
+/// [`IssuePallet::issue_btc`]
+pub const ISSUE_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// vault of `account` with `collateral`, issuing KBTC
+pub fn vault_id(account: AccountId32, collateral: CurrencyId) -> VaultId<AccountId32, CurrencyId> {
+    VaultId {
+        account_id: account,
+        currencies: VaultCurrencyPair {
+            collateral,
+            wrapped: CurrencyId::Token(TokenSymbol::KBTC),
+        },
+    }
+}
+
+/// BTC payment of an issue request, see [`IssuePallet::issue_request_and_pay`].
+#[derive(Debug, Clone)]
+pub struct IssuePayment {
+    pub issue_id: H256,
+    pub btc_txid: Txid,
+    pub btc_paid: Amount,
+}
+
+impl IssuePayment {
+    fn into_receipt(
+        self,
+        issued: IssueRequest<AccountId32, u32, u128, CurrencyId>,
+    ) -> IssueReceipt {
+        log::info!("GGX: Issued {} KBTC, fee {}", issued.amount, issued.fee);
+        IssueReceipt {
+            issue_id: self.issue_id,
+            vault_id: issued.vault,
+            btc_txid: self.btc_txid,
+            btc_paid: self.btc_paid,
+            amount: issued.amount,
+            fee: issued.fee,
+        }
+    }
+}
+
+/// Result of [`IssuePallet::issue_btc`] and [`IssuePallet::issue_btc_and_execute`].
+#[derive(Debug, Clone)]
+pub struct IssueReceipt {
+    pub issue_id: H256,
+    pub vault_id: VaultId<AccountId32, CurrencyId>,
+    /// BTC payment to the vault
+    pub btc_txid: Txid,
+    pub btc_paid: Amount,
+    /// KBTC minted to the requester
+    pub amount: u128,
+    pub fee: u128,
+}
+
+#[async_trait]
+pub trait IssuePallet: SubstrateApi + BtcRelayPallet {
+    async fn issue_request_issue(
+        &self,
+        requester: Keypair,
+        amount: u128,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        griefing_currency: CurrencyId,
+    ) -> RequestIssue {
+        log::info!("GGX: Requesting issue of {} from {:?}", amount, vault_id);
+        let tx = metadata::ggx::tx()
+            .issue()
+            .request_issue(amount, vault_id, griefing_currency);
+        self.send_tx_and_get_events(requester, tx)
+            .await
+            .find_first::<RequestIssue>()
+            .expect("cannot decode RequestIssue event")
+            .expect("no RequestIssue event")
+    }
+
+    async fn issue_execute_issue(
+        &self,
+        executor: Keypair,
+        btc: &BtcNodeContainer,
+        issue_id: H256,
+        btc_txid: &Txid,
+    ) -> ExecuteIssue {
+        let proof = full_transaction_proof(btc, btc_txid).await;
+        let tx = metadata::ggx::tx().issue().execute_issue(issue_id, proof);
+        self.send_tx_and_get_events(executor, tx)
+            .await
+            .find_first::<ExecuteIssue>()
+            .expect("cannot decode ExecuteIssue event")
+            .expect("no ExecuteIssue event")
+    }
+
+    async fn issue_cancel_issue(&self, requester: Keypair, issue_id: H256) {
+        let tx = metadata::ggx::tx().issue().cancel_issue(issue_id);
+        self.send_tx_and_wait_until_finalized(requester, tx).await;
+    }
+
+    async fn issue_get_issue_request(
+        &self,
+        issue_id: H256,
+    ) -> Option<IssueRequest<AccountId32, u32, u128, CurrencyId>> {
+        let query = metadata::ggx::storage().issue().issue_requests(issue_id);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get issue request")
+    }
+
+    /// Request issue from `vault_id`, pay the vault from `wallet` and mine enough confirmations
+    /// for the payment to be executable. The issue is not executed yet.
+    ///
+    /// BTC blocks must be relayed to GGX, by a running vault or with
+    /// [`BtcRelayPallet::btc_relay_relay_blocks`] from another task.
+    async fn issue_request_and_pay(
+        &self,
+        btc: &BtcNodeContainer,
+        wallet: &BtcWallet,
+        requester: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) -> IssuePayment {
+        let griefing_currency = vault_id.currencies.collateral.clone();
+        let request = self
+            .issue_request_issue(requester, amount, vault_id, griefing_currency)
+            .await;
+
+        // the requested amount is paid in BTC, the fee is deducted from the minted amount
+        let btc_paid = Amount::from_sat((request.amount + request.fee) as u64);
+        let btc_txid = wallet
+            .send(&to_btc_address(&request.vault_address.0), btc_paid)
+            .await;
+
+        let confirmations = self.btc_relay_stable_confirmations().await.max(1);
+        btc.mine(confirmations as u64).await;
+        btc.wait_for_confirmations(&btc_txid, confirmations, ISSUE_TIMEOUT)
+            .await;
+        self.btc_relay_wait_for_sync_with(btc, ISSUE_TIMEOUT).await;
+
+        IssuePayment {
+            issue_id: request.issue_id,
+            btc_txid,
+            btc_paid,
+        }
+    }
+
+    /// poll the issue request until it is completed, e.g. by the vault
+    async fn issue_wait_for_completed(
+        &self,
+        issue_id: H256,
+        timeout: Duration,
+    ) -> IssueRequest<AccountId32, u32, u128, CurrencyId> {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let request = self
+                .issue_get_issue_request(issue_id)
+                .await
+                .expect("issue request is not found");
+            match request.status {
+                IssueRequestStatus::Completed => Ok(request),
+                _ => Err(format!("issue {issue_id:?} is not completed yet")),
+            }
+        })
+        .await
+    }
+
+    /// Mint KBTC in one call: request issue from `vault_id`, pay the vault from `wallet`, mine
+    /// enough confirmations and wait until the vault executes the issue.
+    ///
+    /// A vault of `vault_id` must be running, see [`issue_btc_and_execute`](Self::issue_btc_and_execute)
+    /// otherwise.
+    async fn issue_btc(
+        &self,
+        btc: &BtcNodeContainer,
+        wallet: &BtcWallet,
+        requester: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) -> IssueReceipt {
+        let payment = self
+            .issue_request_and_pay(btc, wallet, requester, vault_id, amount)
+            .await;
+        let issued = self
+            .issue_wait_for_completed(payment.issue_id, ISSUE_TIMEOUT)
+            .await;
+        payment.into_receipt(issued)
+    }
+
+    /// Like [`issue_btc`](Self::issue_btc), but `requester` executes the issue itself (unless the
+    /// vault executes it first). BTC blocks must still be relayed to GGX.
+    async fn issue_btc_and_execute(
+        &self,
+        btc: &BtcNodeContainer,
+        wallet: &BtcWallet,
+        requester: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) -> IssueReceipt {
+        let payment = self
+            .issue_request_and_pay(btc, wallet, requester.clone(), vault_id, amount)
+            .await;
+
+        let issue_id = payment.issue_id;
+        let proof = full_transaction_proof(btc, &payment.btc_txid).await;
+        eventually(ISSUE_TIMEOUT, DEFAULT_POLL_INTERVAL, || async {
+            let status = self
+                .issue_get_issue_request(issue_id)
+                .await
+                .expect("issue request is not found")
+                .status;
+            if matches!(status, IssueRequestStatus::Completed) {
+                return Ok(());
+            }
+
+            let tx = metadata::ggx::tx()
+                .issue()
+                .execute_issue(issue_id, proof.clone());
+            match self.try_send_tx(requester.clone(), tx).await {
+                Ok(_) => Ok(()),
+                Err(e) => match module_error(&e) {
+                    // the vault has executed it first
+                    Some((_, error)) if error == "IssueCompleted" => Ok(()),
+                    // relay or parachain has not reached required confirmations yet
+                    Some((_, error)) if error.ends_with("Confirmations") => Err(error),
+                    _ => handle_tx_error(e),
+                },
+            }
+        })
+        .await;
+
+        let issued = self
+            .issue_get_issue_request(issue_id)
+            .await
+            .expect("issue request is not found");
+        payment.into_receipt(issued)
+    }
+}
+
+#[async_trait]
+impl IssuePallet for GgxNodeContainer {}
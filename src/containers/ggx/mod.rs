@@ -1,10 +1,23 @@
 pub mod assets_pallet;
+pub mod bitcoin_types;
+pub mod btc_relay_pallet;
 pub mod dex_pallet;
+pub mod fee_pallet;
+pub mod issue_pallet;
+pub mod nomination_pallet;
+pub mod oracle_pallet;
+pub mod redeem_pallet;
+pub mod replace_pallet;
+pub mod reward_pallet;
+pub mod security_pallet;
+pub mod tokens_pallet;
+pub mod vault_registry_pallet;
 
 use async_trait::async_trait;
 use std::time::Duration;
+use subxt::blocks::ExtrinsicEvents;
 use subxt::{OnlineClient, PolkadotConfig};
-use subxt_signer::sr25519::Keypair;
+use subxt_signer::sr25519::{dev, Keypair};
 use testcontainers::runners::AsyncRunner;
 use testcontainers::{
     core::{Image, WaitFor},
@@ -12,6 +25,8 @@ use testcontainers::{
 };
 use tokio::time::timeout;
 
+use crate::images;
+use crate::logs::ContainerLogs;
 use crate::{handle_tx_error, metadata, vecs};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -160,16 +175,57 @@ pub trait SubstrateApi {
     where
         T: subxt::tx::TxPayload + Sync + Send,
     {
-        let wait = self
-            .api()
+        self.send_tx_and_get_events(owner, payload).await;
+    }
+
+    /// submit tx and return its events once it is finalized, panics if tx failed
+    async fn send_tx_and_get_events<T>(
+        &self,
+        owner: Keypair,
+        payload: T,
+    ) -> ExtrinsicEvents<PolkadotConfig>
+    where
+        T: subxt::tx::TxPayload + Sync + Send,
+    {
+        match self.try_send_tx(owner, payload).await {
+            Ok(events) => events,
+            Err(e) => handle_tx_error(e),
+        }
+    }
+
+    /// like [`send_tx_and_get_events`](Self::send_tx_and_get_events), but returns the error,
+    /// see [`crate::module_error`]
+    async fn try_send_tx<T>(
+        &self,
+        owner: Keypair,
+        payload: T,
+    ) -> Result<ExtrinsicEvents<PolkadotConfig>, subxt::Error>
+    where
+        T: subxt::tx::TxPayload + Sync + Send,
+    {
+        self.api()
             .tx()
             .sign_and_submit_then_watch_default(&payload, &owner)
+            .await?
+            .wait_for_finalized_success()
             .await
-            .expect("cannot submit tx");
+    }
 
-        // this panics if tx was not accepted
-        if let Err(e) = wait.wait_for_finalized_success().await {
-            handle_tx_error(e);
+    /// Dispatch `call` as root, signed by Alice who is the sudo key of dev chains.
+    /// Panics if the call itself fails, not only the sudo extrinsic.
+    async fn sudo(
+        &self,
+        call: metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall,
+    ) {
+        let tx = metadata::ggx::tx().sudo().sudo(call);
+        let sudid = self
+            .send_tx_and_get_events(dev::alice(), tx)
+            .await
+            .find_first::<metadata::ggx::sudo::events::Sudid>()
+            .expect("cannot decode Sudid event")
+            .expect("no Sudid event");
+        if let Err(e) = sudid.sudo_result {
+            panic!("Sudo call failed with an error: {:?}", e);
         }
     }
 }
@@ -181,6 +237,22 @@ impl SubstrateApi for GgxNodeContainer {
     }
 }
 
+impl ContainerLogs for GgxNodeContainer {
+    fn container_id(&self) -> &str {
+        self.container.id()
+    }
+
+    fn log_name(&self) -> &str {
+        "ggx"
+    }
+}
+
+impl Drop for GgxNodeContainer {
+    fn drop(&mut self) {
+        crate::logs::forget(self.container_id());
+    }
+}
+
 impl GgxNodeContainer {
     pub async fn from(container: ContainerAsync<GgxNodeImage>) -> Self {
         Self::from_inner(container, false).await
@@ -196,6 +268,7 @@ impl GgxNodeContainer {
             host_network,
             api: None,
         };
+        result.logs();
 
         let api = OnlineClient::<PolkadotConfig>::from_url(result.get_host_ws_url().await)
             .await
@@ -273,6 +346,7 @@ pub async fn start_ggx(extraargs: Vec<String>) -> GgxNodeContainer {
     args.args.extend(extraargs);
 
     let image = GgxNodeImage::brooklyn();
+    images::require(&image).await;
     let image = RunnableImage::from((image, args)).with_network("host");
 
     GgxNodeContainer::from_with_host_network(image.start().await).await
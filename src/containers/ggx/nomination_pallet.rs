@@ -0,0 +1,289 @@
+use crate::containers::ggx::tokens_pallet::TokensPallet;
+use crate::containers::ggx::vault_registry_pallet::{fixed_mul, FIXED_ONE};
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{
+    CurrencyId, VaultCurrencyPair, VaultId,
+};
+use async_trait::async_trait;
+use subxt::utils::AccountId32;
+use subxt_signer::sr25519::Keypair;
+
+type RuntimeCall = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+type NominationCall = metadata::ggx::runtime_types::nomination::pallet::Call;
+
+/// A vault backed by nominators, see [`NominationPallet::nomination_nominate_vault`].
+#[derive(Clone)]
+pub struct NominatedVault {
+    pub vault_id: VaultId<AccountId32, CurrencyId>,
+    /// nominators with the collateral they deposited
+    pub nominators: Vec<(Keypair, u128)>,
+}
+
+impl NominatedVault {
+    /// collateral deposited by all nominators
+    pub fn nominated_collateral(&self) -> u128 {
+        self.nominators.iter().map(|(_, amount)| amount).sum()
+    }
+}
+
+#[async_trait]
+pub trait NominationPallet: SubstrateApi + TokensPallet {
+    async fn nomination_set_enabled(&self, enabled: bool) {
+        log::info!("GGX: Setting nomination enabled to {}", enabled);
+        self.sudo(RuntimeCall::Nomination(
+            NominationCall::set_nomination_enabled { enabled },
+        ))
+        .await;
+    }
+
+    async fn nomination_is_enabled(&self) -> bool {
+        let query = metadata::ggx::storage().nomination().nomination_enabled();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get nomination enabled")
+    }
+
+    /// Allow others to nominate the vault of `vault` with `currency_pair`, nomination must be
+    /// enabled.
+    async fn nomination_opt_in(
+        &self,
+        vault: Keypair,
+        currency_pair: VaultCurrencyPair<CurrencyId>,
+    ) {
+        log::info!("GGX: Opting in to nomination {:?}", currency_pair);
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .opt_in_to_nomination(currency_pair);
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+
+    /// Stop nomination, nominated collateral is refunded.
+    async fn nomination_opt_out(
+        &self,
+        vault: Keypair,
+        currency_pair: VaultCurrencyPair<CurrencyId>,
+    ) {
+        log::info!("GGX: Opting out of nomination {:?}", currency_pair);
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .opt_out_of_nomination(currency_pair);
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+
+    async fn nomination_is_opted_in(&self, vault_id: VaultId<AccountId32, CurrencyId>) -> bool {
+        let query = metadata::ggx::storage().nomination().vaults(vault_id);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get nomination opt in")
+    }
+
+    /// Set the maximum collateral the vault of `vault` accepts from nominators.
+    async fn nomination_set_limit(
+        &self,
+        vault: Keypair,
+        currency_pair: VaultCurrencyPair<CurrencyId>,
+        limit: u128,
+    ) {
+        log::info!(
+            "GGX: Setting nomination limit of {:?} to {}",
+            currency_pair,
+            limit
+        );
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .set_nomination_limit(currency_pair, limit);
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+
+    async fn nomination_limit(&self, vault_id: VaultId<AccountId32, CurrencyId>) -> u128 {
+        let query = metadata::ggx::storage()
+            .nomination()
+            .nomination_limit(vault_id);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get nomination limit")
+    }
+
+    async fn nomination_deposit_collateral(
+        &self,
+        nominator: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) {
+        log::info!("GGX: Nominating {} collateral to {:?}", amount, vault_id);
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .deposit_collateral(vault_id, amount);
+        self.send_tx_and_wait_until_finalized(nominator, tx).await;
+    }
+
+    async fn nomination_withdraw_collateral(
+        &self,
+        nominator: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) {
+        log::info!(
+            "GGX: Withdrawing {} nominated collateral from {:?}",
+            amount,
+            vault_id
+        );
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .withdraw_collateral(vault_id, Some(amount), None);
+        self.send_tx_and_wait_until_finalized(nominator, tx).await;
+    }
+
+    /// collateral of `nominator` in `vault_id`, not counting slashes
+    async fn nomination_stake(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        nominator: AccountId32,
+    ) -> u128 {
+        let storage = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest");
+        let nonce = storage
+            .fetch_or_default(
+                &metadata::ggx::storage()
+                    .vault_staking()
+                    .nonce(vault_id.clone()),
+            )
+            .await
+            .expect("cannot get vault staking nonce");
+        let stake = storage
+            .fetch_or_default(
+                &metadata::ggx::storage()
+                    .vault_staking()
+                    .stake(nonce, (vault_id, nominator)),
+            )
+            .await
+            .expect("cannot get nominator stake");
+        (stake.0.max(0) as u128) / FIXED_ONE
+    }
+
+    /// Rewards in `currency` of `nominator` that are distributed to the vault's stakers, but
+    /// not withdrawn yet. Fees reach the stakers only on a withdrawal from the vault, see
+    /// [`nomination_withdraw_rewards`](Self::nomination_withdraw_rewards).
+    async fn nomination_reward(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        nominator: AccountId32,
+        currency: CurrencyId,
+    ) -> u128 {
+        let storage = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest");
+        let staking = metadata::ggx::storage().vault_staking();
+        let nonce = storage
+            .fetch_or_default(&staking.nonce(vault_id.clone()))
+            .await
+            .expect("cannot get vault staking nonce");
+        let stake = storage
+            .fetch_or_default(&staking.stake(nonce, (vault_id.clone(), nominator.clone())))
+            .await
+            .expect("cannot get nominator stake");
+        let reward_per_token = storage
+            .fetch_or_default(
+                &staking.reward_per_token(currency.clone(), (nonce, vault_id.clone())),
+            )
+            .await
+            .expect("cannot get reward per token");
+        let tally = storage
+            .fetch_or_default(&staking.reward_tally(currency, (nonce, vault_id, nominator)))
+            .await
+            .expect("cannot get reward tally");
+        let reward = fixed_mul(stake.0, reward_per_token.0) - tally.0;
+        (reward.max(0) as u128) / FIXED_ONE
+    }
+
+    /// Withdraw all rewards of `nominator` (or the vault itself) from `vault_id`.
+    async fn nomination_withdraw_rewards(
+        &self,
+        nominator: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) {
+        log::info!("GGX: Withdrawing rewards from {:?}", vault_id);
+        let tx = metadata::ggx::tx().fee().withdraw_rewards(vault_id, None);
+        self.send_tx_and_wait_until_finalized(nominator, tx).await;
+    }
+
+    /// Let `nominators` back the registered vault `vault_id` of `vault`, each with its amount of
+    /// collateral. Enables nomination, opts the vault in and raises its limit as needed.
+    async fn nomination_nominate_vault(
+        &self,
+        vault: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        nominators: Vec<(Keypair, u128)>,
+    ) -> NominatedVault {
+        let nominated = NominatedVault {
+            vault_id: vault_id.clone(),
+            nominators,
+        };
+        let pair = vault_id.currencies.clone();
+
+        if !self.nomination_is_enabled().await {
+            self.nomination_set_enabled(true).await;
+        }
+        if !self.nomination_is_opted_in(vault_id.clone()).await {
+            self.nomination_opt_in(vault.clone(), pair.clone()).await;
+        }
+        let limit = self.nomination_limit(vault_id.clone()).await;
+        if limit < nominated.nominated_collateral() {
+            self.nomination_set_limit(vault, pair, nominated.nominated_collateral())
+                .await;
+        }
+
+        for (nominator, amount) in &nominated.nominators {
+            self.nomination_deposit_collateral(nominator.clone(), vault_id.clone(), *amount)
+                .await;
+        }
+        nominated
+    }
+
+    /// Withdraw the rewards in `currency` of every nominator of `nominated`, returns the amount
+    /// each of them received, in order.
+    async fn nomination_withdraw_all_rewards(
+        &self,
+        nominated: &NominatedVault,
+        currency: CurrencyId,
+    ) -> Vec<u128> {
+        let mut received = vec![];
+        for (nominator, _) in &nominated.nominators {
+            let account = nominator.public_key().to_account_id();
+            let before = self
+                .tokens_free_balance(account.clone(), currency.clone())
+                .await;
+            self.nomination_withdraw_rewards(nominator.clone(), nominated.vault_id.clone())
+                .await;
+            let after = self.tokens_free_balance(account, currency.clone()).await;
+            received.push(after.saturating_sub(before));
+        }
+        received
+    }
+}
+
+#[async_trait]
+impl NominationPallet for GgxNodeContainer {}
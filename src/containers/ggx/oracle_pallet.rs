@@ -0,0 +1,299 @@
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::oracle::Key;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{CurrencyId, TokenSymbol};
+use crate::metadata::ggx::runtime_types::oracle::TimestampedValue;
+use crate::metadata::ggx::runtime_types::sp_arithmetic::fixed_point::FixedU128;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use subxt::ext::codec::Encode;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, PolkadotConfig};
+use subxt_signer::sr25519::Keypair;
+use tokio::task::JoinHandle;
+
+type RuntimeCall = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+type OracleCall = metadata::ggx::runtime_types::oracle::pallet::Call;
+
+/// Values fed by a [`FakeOracle`] at once.
+pub type OracleValues = Vec<(Key, FixedU128)>;
+
+/// oracle key of the exchange rate of `token`, in its planck per satoshi
+pub fn exchange_rate_key(token: TokenSymbol) -> Key {
+    Key::ExchangeRate(CurrencyId::Token(token))
+}
+
+/// `steps` values of `key` moving linearly from `from` to `to`, both included.
+/// Feed them with [`FakeOracle::follow`], e.g. to drop a collateral price below the liquidation
+/// threshold.
+pub fn linear_curve(key: Key, from: FixedU128, to: FixedU128, steps: usize) -> Vec<OracleValues> {
+    assert!(steps >= 2, "a curve needs at least 2 steps");
+    let (from, to) = (from.0 as i128, to.0 as i128);
+    let last = (steps - 1) as i128;
+    (0..=last)
+        .map(|i| {
+            let value = from + (to - from) * i / last;
+            vec![(key.clone(), FixedU128(value as u128))]
+        })
+        .collect()
+}
+
+/// Replace the value of `key` in `values` or add it, runtime types don't implement PartialEq.
+fn upsert(values: &mut OracleValues, key: Key, value: FixedU128) {
+    let encoded = key.encode();
+    values.retain(|(k, _)| k.encode() != encoded);
+    values.push((key, value));
+}
+
+/// Feeds oracle values every interval until dropped, so they don't expire.
+pub struct FakeOracle {
+    values: Arc<Mutex<OracleValues>>,
+    script: Arc<Mutex<VecDeque<OracleValues>>>,
+    fed: Arc<AtomicU64>,
+    handle: JoinHandle<()>,
+}
+
+impl FakeOracle {
+    /// Feed `value` of `key` from the next feed on.
+    pub fn set(&self, key: Key, value: FixedU128) {
+        log::info!("Fake oracle: setting {:?} to {:?}", key, value);
+        upsert(&mut self.values.lock().unwrap(), key, value);
+    }
+
+    /// Replace values with one step of `curve` per feed, values of the last step are kept after.
+    pub fn follow(&self, curve: impl IntoIterator<Item = OracleValues>) {
+        let mut script = self.script.lock().unwrap();
+        script.clear();
+        script.extend(curve);
+    }
+
+    /// whether all steps passed to [`follow`](Self::follow) have been fed
+    pub fn is_script_done(&self) -> bool {
+        self.script.lock().unwrap().is_empty()
+    }
+
+    /// number of successful feeds so far
+    pub fn feeds(&self) -> u64 {
+        self.fed.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FakeOracle {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn feed(
+    api: &OnlineClient<PolkadotConfig>,
+    oracle: &Keypair,
+    values: OracleValues,
+) -> Result<(), subxt::Error> {
+    let tx = metadata::ggx::tx().oracle().feed_values(values);
+    api.tx()
+        .sign_and_submit_then_watch_default(&tx, oracle)
+        .await?
+        .wait_for_finalized_success()
+        .await?;
+    Ok(())
+}
+
+#[async_trait]
+pub trait OraclePallet: SubstrateApi {
+    async fn oracle_feed_values(&self, oracle: Keypair, values: OracleValues) {
+        log::info!("GGX: Feeding oracle values {:?}", values);
+        let tx = metadata::ggx::tx().oracle().feed_values(values);
+        self.send_tx_and_wait_until_finalized(oracle, tx).await;
+    }
+
+    /// price of 1 satoshi in planck of `currency`
+    async fn oracle_feed_exchange_rate(
+        &self,
+        oracle: Keypair,
+        currency: CurrencyId,
+        rate: FixedU128,
+    ) {
+        self.oracle_feed_values(oracle, vec![(Key::ExchangeRate(currency), rate)])
+            .await;
+    }
+
+    /// BTC fee estimate in satoshi per byte
+    async fn oracle_feed_fee_estimate(&self, oracle: Keypair, fee: FixedU128) {
+        self.oracle_feed_values(oracle, vec![(Key::FeeEstimation, fee)])
+            .await;
+    }
+
+    /// aggregate of `key` over all oracles, updated in the block after a feed
+    async fn oracle_aggregate(&self, key: Key) -> Option<FixedU128> {
+        let query = metadata::ggx::storage().oracle().aggregate(key);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get oracle aggregate")
+    }
+
+    async fn oracle_exchange_rate(&self, currency: CurrencyId) -> Option<FixedU128> {
+        self.oracle_aggregate(Key::ExchangeRate(currency)).await
+    }
+
+    async fn oracle_fee_estimate(&self) -> Option<FixedU128> {
+        self.oracle_aggregate(Key::FeeEstimation).await
+    }
+
+    /// last value of `key` fed by `oracle`
+    async fn oracle_raw_value(
+        &self,
+        key: Key,
+        oracle: AccountId32,
+    ) -> Option<TimestampedValue<FixedU128, u64>> {
+        let query = metadata::ggx::storage().oracle().raw_values(key, oracle);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get oracle raw value")
+    }
+
+    /// Wait until the aggregate of `key` is `value`.
+    async fn oracle_wait_for_aggregate(&self, key: Key, value: FixedU128, timeout: Duration) {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            match self.oracle_aggregate(key.clone()).await {
+                Some(v) if v.0 == value.0 => Ok(()),
+                other => Err(other),
+            }
+        })
+        .await
+    }
+
+    /// authorized oracles with their names
+    async fn oracle_authorized_oracles(&self) -> Vec<(AccountId32, String)> {
+        let query = metadata::ggx::storage().oracle().authorized_oracles_root();
+
+        let mut it = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .iter(query, 100)
+            .await
+            .expect("cannot iter");
+
+        let mut oracles = vec![];
+        while let Ok(Some((key, name))) = it.next().await {
+            // the account id is appended to the hashed storage key
+            let account: [u8; 32] = key.0[key.0.len() - 32..].try_into().unwrap();
+            oracles.push((
+                AccountId32(account),
+                String::from_utf8_lossy(&name.0).to_string(),
+            ));
+        }
+        oracles
+    }
+
+    async fn oracle_insert_authorized_oracle(&self, account: AccountId32, name: &str) {
+        log::info!("GGX: Authorizing oracle {} ({})", name, account);
+        self.sudo(RuntimeCall::Oracle(OracleCall::insert_authorized_oracle {
+            account_id: account,
+            name: BoundedVec(name.as_bytes().to_vec()),
+        }))
+        .await;
+    }
+
+    async fn oracle_remove_authorized_oracle(&self, account: AccountId32) {
+        log::info!("GGX: Removing oracle {}", account);
+        self.sudo(RuntimeCall::Oracle(OracleCall::remove_authorized_oracle {
+            account_id: account,
+        }))
+        .await;
+    }
+
+    /// Feed `values` with `oracle` now and then every `interval` in background, until the
+    /// returned [`FakeOracle`] is dropped. `oracle` must be authorized.
+    async fn oracle_start_fake_oracle(
+        &self,
+        oracle: Keypair,
+        interval: Duration,
+        values: OracleValues,
+    ) -> FakeOracle {
+        log::info!("Starting fake oracle, feeding every {:?}", interval);
+        let api = self.api().clone();
+        let values = Arc::new(Mutex::new(values));
+        let script = Arc::new(Mutex::new(VecDeque::new()));
+        let fed = Arc::new(AtomicU64::new(0));
+
+        let handle = tokio::spawn({
+            let values = values.clone();
+            let script = script.clone();
+            let fed = fed.clone();
+            async move {
+                let mut ticks = tokio::time::interval(interval);
+                loop {
+                    ticks.tick().await;
+                    let current = {
+                        let mut values = values.lock().unwrap();
+                        if let Some(step) = script.lock().unwrap().pop_front() {
+                            for (key, value) in step {
+                                upsert(&mut values, key, value);
+                            }
+                        }
+                        values.clone()
+                    };
+
+                    match feed(&api, &oracle, current).await {
+                        Ok(()) => {
+                            fed.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Err(e) => log::warn!("Fake oracle cannot feed values: {}", e),
+                    }
+                }
+            }
+        });
+
+        FakeOracle {
+            values,
+            script,
+            fed,
+            handle,
+        }
+    }
+}
+
+#[async_trait]
+impl OraclePallet for GgxNodeContainer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::ggx::runtime_types::interbtc_primitives::TokenSymbol;
+
+    #[test]
+    fn test_linear_curve() {
+        let key = Key::ExchangeRate(CurrencyId::Token(TokenSymbol::GGXT));
+        let curve = linear_curve(key, FixedU128(100), FixedU128(40), 4);
+        let values: Vec<u128> = curve.iter().map(|step| step[0].1 .0).collect();
+        assert_eq!(values, vec![100, 80, 60, 40]);
+    }
+
+    #[test]
+    fn test_upsert() {
+        let ggxt = || Key::ExchangeRate(CurrencyId::Token(TokenSymbol::GGXT));
+        let mut values = vec![(ggxt(), FixedU128(1)), (Key::FeeEstimation, FixedU128(2))];
+        upsert(&mut values, ggxt(), FixedU128(3));
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1].0.encode(), ggxt().encode());
+        assert_eq!(values[1].1 .0, 3);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::containers::btc::BtcNodeContainer;
+use crate::containers::ggx::bitcoin_types::{
+    full_transaction_proof, to_btc_address, to_interbtc_address,
+};
+use crate::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::redeem::events::{
+    CancelRedeem, ExecuteRedeem, LiquidationRedeem, RequestRedeem,
+};
+use crate::metadata::ggx::runtime_types::interbtc_primitives::redeem::{
+    RedeemRequest, RedeemRequestStatus,
+};
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{
+    CurrencyId, VaultCurrencyPair, VaultId,
+};
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::{Address, Amount, Transaction, Txid};
+use bitcoincore_rpc::{Client, RpcApi};
+use std::time::Duration;
+use subxt::utils::{AccountId32, H256};
+use subxt_signer::sr25519::Keypair;
+
+/// Vault payment of a redeem request found by [`RedeemPallet::redeem_wait_for_payment`].
+#[derive(Debug, Clone)]
+pub struct RedeemPayment {
+    pub txid: Txid,
+    pub tx: Transaction,
+    /// amount received by the redeemer, i.e. redeemed amount minus fees
+    pub amount: Amount,
+}
+
+/// Amount `tx` pays to `address` if it commits to `redeem_id` in an OP_RETURN output.
+fn redeem_payment_amount(tx: &Transaction, redeem_id: H256, address: &Address) -> Option<Amount> {
+    let commits = tx.output.iter().any(|o| {
+        o.script_pubkey.is_op_return()
+            && o.script_pubkey.as_bytes().get(2..) == Some(&redeem_id.0[..])
+    });
+    let script = address.script_pubkey();
+    let paid: Amount = tx
+        .output
+        .iter()
+        .filter(|o| o.script_pubkey == script)
+        .map(|o| o.value)
+        .sum();
+    commits.then_some(paid)
+}
+
+/// Search the mempool and blocks from `from_height` for the payment of `redeem_id`.
+fn find_redeem_payment(
+    c: &Client,
+    from_height: u64,
+    redeem_id: H256,
+    address: &Address,
+) -> bitcoincore_rpc::Result<Option<(Txid, Transaction)>> {
+    for txid in c.get_raw_mempool()? {
+        let tx = c.get_raw_transaction(&txid, None)?;
+        if redeem_payment_amount(&tx, redeem_id, address).is_some() {
+            return Ok(Some((txid, tx)));
+        }
+    }
+    for height in from_height..=c.get_block_count()? {
+        let block = c.get_block(&c.get_block_hash(height)?)?;
+        let found = block
+            .txdata
+            .into_iter()
+            .find(|tx| redeem_payment_amount(tx, redeem_id, address).is_some());
+        if let Some(tx) = found {
+            return Ok(Some((tx.txid(), tx)));
+        }
+    }
+    Ok(None)
+}
+
+#[async_trait]
+pub trait RedeemPallet: SubstrateApi + BtcRelayPallet {
+    async fn redeem_request_redeem(
+        &self,
+        redeemer: Keypair,
+        amount: u128,
+        btc_address: &Address,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> RequestRedeem {
+        log::info!(
+            "GGX: Requesting redeem of {} to {} from {:?}",
+            amount,
+            btc_address,
+            vault_id
+        );
+        let tx = metadata::ggx::tx().redeem().request_redeem(
+            amount,
+            subxt::utils::Static(to_interbtc_address(btc_address)),
+            vault_id,
+        );
+        self.send_tx_and_get_events(redeemer, tx)
+            .await
+            .find_first::<RequestRedeem>()
+            .expect("cannot decode RequestRedeem event")
+            .expect("no RequestRedeem event")
+    }
+
+    async fn redeem_execute_redeem(
+        &self,
+        executor: Keypair,
+        btc: &BtcNodeContainer,
+        redeem_id: H256,
+        btc_txid: &Txid,
+    ) -> ExecuteRedeem {
+        let proof = full_transaction_proof(btc, btc_txid).await;
+        let tx = metadata::ggx::tx()
+            .redeem()
+            .execute_redeem(redeem_id, proof);
+        self.send_tx_and_get_events(executor, tx)
+            .await
+            .find_first::<ExecuteRedeem>()
+            .expect("cannot decode ExecuteRedeem event")
+            .expect("no ExecuteRedeem event")
+    }
+
+    /// Cancel an expired redeem request, `reimburse` takes the vault collateral instead of
+    /// getting the KBTC back.
+    async fn redeem_cancel_redeem(
+        &self,
+        redeemer: Keypair,
+        redeem_id: H256,
+        reimburse: bool,
+    ) -> CancelRedeem {
+        let tx = metadata::ggx::tx()
+            .redeem()
+            .cancel_redeem(redeem_id, reimburse);
+        self.send_tx_and_get_events(redeemer, tx)
+            .await
+            .find_first::<CancelRedeem>()
+            .expect("cannot decode CancelRedeem event")
+            .expect("no CancelRedeem event")
+    }
+
+    /// Burn KBTC for collateral of liquidated vaults.
+    async fn redeem_liquidation_redeem(
+        &self,
+        redeemer: Keypair,
+        currencies: VaultCurrencyPair<CurrencyId>,
+        amount: u128,
+    ) -> LiquidationRedeem {
+        let tx = metadata::ggx::tx()
+            .redeem()
+            .liquidation_redeem(currencies, amount);
+        self.send_tx_and_get_events(redeemer, tx)
+            .await
+            .find_first::<LiquidationRedeem>()
+            .expect("cannot decode LiquidationRedeem event")
+            .expect("no LiquidationRedeem event")
+    }
+
+    async fn redeem_get_redeem_request(
+        &self,
+        redeem_id: H256,
+    ) -> Option<RedeemRequest<AccountId32, u32, u128, CurrencyId>> {
+        let query = metadata::ggx::storage().redeem().redeem_requests(redeem_id);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get redeem request")
+    }
+
+    /// Wait until the vault pays redeem request `redeem_id` on `btc`, in the mempool or in a
+    /// block. The payment must send the redeemed amount minus fees to the redeemer's address and
+    /// include the redeem id in an OP_RETURN output.
+    async fn redeem_wait_for_payment(
+        &self,
+        btc: &BtcNodeContainer,
+        redeem_id: H256,
+        timeout: Duration,
+    ) -> RedeemPayment {
+        let request = self
+            .redeem_get_redeem_request(redeem_id)
+            .await
+            .expect("redeem request is not found");
+        let address = to_btc_address(&request.btc_address.0);
+        let expected = Amount::from_sat(request.amount_btc as u64);
+        // the relay lags behind BTC, so the payment cannot be in a block below its height
+        let from_height = request.btc_height as u64;
+        log::info!(
+            "Waiting for BTC payment of {} to {} for redeem {:?}",
+            expected,
+            address,
+            redeem_id
+        );
+
+        let (txid, tx) = eventually(timeout, DEFAULT_POLL_INTERVAL, || {
+            let address = address.clone();
+            async move {
+                btc.rpc(move |c| find_redeem_payment(c, from_height, redeem_id, &address))
+                    .await
+                    .expect("cannot search for redeem payment")
+                    .ok_or("no redeem payment yet")
+            }
+        })
+        .await;
+
+        let amount = redeem_payment_amount(&tx, redeem_id, &address).unwrap();
+        assert_eq!(
+            amount, expected,
+            "redeem payment {txid} does not match the requested amount minus fees"
+        );
+        log::info!("Found BTC redeem payment {}", txid);
+        RedeemPayment { txid, tx, amount }
+    }
+
+    /// Wait until redeem request `redeem_id` is executed, e.g. by the vault.
+    async fn redeem_wait_for_completed(&self, redeem_id: H256, timeout: Duration) {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let status = self
+                .redeem_get_redeem_request(redeem_id)
+                .await
+                .expect("redeem request is not found")
+                .status;
+            match status {
+                RedeemRequestStatus::Completed => Ok(()),
+                other => Err(other),
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl RedeemPallet for GgxNodeContainer {}
+
+#[cfg(test)]
+mod tests {
+    use bitcoincore_rpc::bitcoin::{absolute, transaction::Version, Network, ScriptBuf, TxOut};
+
+    use super::*;
+
+    #[test]
+    fn test_redeem_payment_amount() {
+        let address = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"
+            .parse::<Address<_>>()
+            .unwrap()
+            .require_network(Network::Regtest)
+            .unwrap();
+        let redeem_id = H256([7; 32]);
+        let mut tx = Transaction {
+            version: Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::from_sat(1000),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        assert_eq!(redeem_payment_amount(&tx, redeem_id, &address), None);
+
+        tx.output.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::new_op_return(&redeem_id.0),
+        });
+        assert_eq!(
+            redeem_payment_amount(&tx, redeem_id, &address),
+            Some(Amount::from_sat(1000))
+        );
+        assert_eq!(redeem_payment_amount(&tx, H256([8; 32]), &address), None);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::containers::btc::BtcNodeContainer;
+use crate::containers::ggx::bitcoin_types::{full_transaction_proof, to_interbtc_address};
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::replace::events::{
+    AcceptReplace, CancelReplace, ExecuteReplace, RequestReplace, WithdrawReplace,
+};
+use crate::metadata::ggx::runtime_types::interbtc_primitives::replace::{
+    ReplaceRequest, ReplaceRequestStatus,
+};
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{CurrencyId, VaultId};
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::{Address, Txid};
+use std::time::Duration;
+use subxt::ext::codec::Encode;
+use subxt::utils::{AccountId32, H256};
+use subxt_signer::sr25519::Keypair;
+
+/// Result of [`ReplacePallet::replace_vault`].
+#[derive(Debug, Clone)]
+pub struct ReplaceReceipt {
+    pub replace_id: H256,
+    pub old_vault_id: VaultId<AccountId32, CurrencyId>,
+    pub new_vault_id: VaultId<AccountId32, CurrencyId>,
+    /// KBTC moved from the old vault to the new one
+    pub amount: u128,
+    /// collateral locked by the new vault
+    pub collateral: u128,
+}
+
+#[async_trait]
+pub trait ReplacePallet: SubstrateApi {
+    /// Ask other vaults to take over `amount` KBTC of `old_vault`.
+    async fn replace_request_replace(
+        &self,
+        old_vault: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) -> RequestReplace {
+        log::info!("GGX: Requesting replace of {} from {:?}", amount, vault_id);
+        let tx = metadata::ggx::tx()
+            .replace()
+            .request_replace(vault_id.currencies, amount);
+        self.send_tx_and_get_events(old_vault, tx)
+            .await
+            .find_first::<RequestReplace>()
+            .expect("cannot decode RequestReplace event")
+            .expect("no RequestReplace event")
+    }
+
+    async fn replace_withdraw_replace(
+        &self,
+        old_vault: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) -> WithdrawReplace {
+        let tx = metadata::ggx::tx()
+            .replace()
+            .withdraw_replace(vault_id.currencies, amount);
+        self.send_tx_and_get_events(old_vault, tx)
+            .await
+            .find_first::<WithdrawReplace>()
+            .expect("cannot decode WithdrawReplace event")
+            .expect("no WithdrawReplace event")
+    }
+
+    /// Take over `amount_btc` of `old_vault_id` locking `collateral`, the old vault pays the BTC
+    /// to `btc_address`.
+    async fn replace_accept_replace(
+        &self,
+        new_vault: Keypair,
+        new_vault_id: VaultId<AccountId32, CurrencyId>,
+        old_vault_id: VaultId<AccountId32, CurrencyId>,
+        amount_btc: u128,
+        collateral: u128,
+        btc_address: &Address,
+    ) -> AcceptReplace {
+        log::info!(
+            "GGX: Accepting replace of {} from {:?} by {:?}",
+            amount_btc,
+            old_vault_id,
+            new_vault_id
+        );
+        let tx = metadata::ggx::tx().replace().accept_replace(
+            new_vault_id.currencies,
+            old_vault_id,
+            amount_btc,
+            collateral,
+            subxt::utils::Static(to_interbtc_address(btc_address)),
+        );
+        self.send_tx_and_get_events(new_vault, tx)
+            .await
+            .find_first::<AcceptReplace>()
+            .expect("cannot decode AcceptReplace event")
+            .expect("no AcceptReplace event")
+    }
+
+    async fn replace_execute_replace(
+        &self,
+        executor: Keypair,
+        btc: &BtcNodeContainer,
+        replace_id: H256,
+        btc_txid: &Txid,
+    ) -> ExecuteReplace {
+        let proof = full_transaction_proof(btc, btc_txid).await;
+        let tx = metadata::ggx::tx()
+            .replace()
+            .execute_replace(replace_id, proof);
+        self.send_tx_and_get_events(executor, tx)
+            .await
+            .find_first::<ExecuteReplace>()
+            .expect("cannot decode ExecuteReplace event")
+            .expect("no ExecuteReplace event")
+    }
+
+    /// Cancel an expired replace request, the old vault's griefing collateral goes to the new one.
+    async fn replace_cancel_replace(&self, new_vault: Keypair, replace_id: H256) -> CancelReplace {
+        let tx = metadata::ggx::tx().replace().cancel_replace(replace_id);
+        self.send_tx_and_get_events(new_vault, tx)
+            .await
+            .find_first::<CancelReplace>()
+            .expect("cannot decode CancelReplace event")
+            .expect("no CancelReplace event")
+    }
+
+    async fn replace_get_replace_request(
+        &self,
+        replace_id: H256,
+    ) -> Option<ReplaceRequest<AccountId32, u32, u128, CurrencyId>> {
+        let query = metadata::ggx::storage()
+            .replace()
+            .replace_requests(replace_id);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get replace request")
+    }
+
+    /// all replace requests with their ids
+    async fn replace_get_replace_requests(
+        &self,
+    ) -> Vec<(H256, ReplaceRequest<AccountId32, u32, u128, CurrencyId>)> {
+        let query = metadata::ggx::storage().replace().replace_requests_root();
+
+        let mut it = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .iter(query, 100)
+            .await
+            .expect("cannot iter");
+
+        let mut requests = vec![];
+        while let Ok(Some((key, request))) = it.next().await {
+            // the id is appended to the hashed storage key
+            let id = H256::from_slice(&key.0[key.0.len() - 32..]);
+            requests.push((id, request));
+        }
+        requests
+    }
+
+    /// replace requests where `vault_id` is the old or the new vault
+    async fn replace_get_vault_replace_requests(
+        &self,
+        vault_id: &VaultId<AccountId32, CurrencyId>,
+    ) -> Vec<(H256, ReplaceRequest<AccountId32, u32, u128, CurrencyId>)> {
+        // runtime types don't implement PartialEq
+        let vault_id = vault_id.encode();
+        self.replace_get_replace_requests()
+            .await
+            .into_iter()
+            .filter(|(_, r)| r.old_vault.encode() == vault_id || r.new_vault.encode() == vault_id)
+            .collect()
+    }
+
+    async fn replace_replace_period(&self) -> u32 {
+        let query = metadata::ggx::storage().replace().replace_period();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get replace period")
+    }
+
+    /// Wait until replace request `replace_id` is executed, e.g. by the old vault.
+    async fn replace_wait_for_completed(&self, replace_id: H256, timeout: Duration) {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            let status = self
+                .replace_get_replace_request(replace_id)
+                .await
+                .expect("replace request is not found")
+                .status;
+            match status {
+                ReplaceRequestStatus::Completed => Ok(()),
+                other => Err(other),
+            }
+        })
+        .await
+    }
+
+    /// Move `amount` KBTC from `old_vault` to `new_vault`: request and accept the replace, then
+    /// wait until the old vault's client pays `btc_address` and executes it.
+    ///
+    /// Both vaults must be registered and the old vault's client must be running. The new
+    /// vault's client should not auto-accept replace requests, otherwise it races with this call.
+    #[allow(clippy::too_many_arguments)]
+    async fn replace_vault(
+        &self,
+        old_vault: Keypair,
+        old_vault_id: VaultId<AccountId32, CurrencyId>,
+        new_vault: Keypair,
+        new_vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+        collateral: u128,
+        btc_address: &Address,
+        timeout: Duration,
+    ) -> ReplaceReceipt {
+        let requested = self
+            .replace_request_replace(old_vault, old_vault_id.clone(), amount)
+            .await;
+        let accepted = self
+            .replace_accept_replace(
+                new_vault,
+                new_vault_id,
+                old_vault_id,
+                requested.amount,
+                collateral,
+                btc_address,
+            )
+            .await;
+
+        self.replace_wait_for_completed(accepted.replace_id, timeout)
+            .await;
+        log::info!(
+            "GGX: Replaced {} KBTC of {:?} by {:?}",
+            accepted.amount,
+            accepted.old_vault_id,
+            accepted.new_vault_id
+        );
+
+        ReplaceReceipt {
+            replace_id: accepted.replace_id,
+            old_vault_id: accepted.old_vault_id,
+            new_vault_id: accepted.new_vault_id,
+            amount: accepted.amount,
+            collateral: accepted.collateral,
+        }
+    }
+}
+
+#[async_trait]
+impl ReplacePallet for GgxNodeContainer {}
@@ -0,0 +1,117 @@
+use crate::containers::ggx::vault_registry_pallet::{fixed_mul, FIXED_ONE};
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{CurrencyId, VaultId};
+use async_trait::async_trait;
+use subxt::utils::AccountId32;
+
+/// Signed fixed point value of the reward pallet as an amount, negative values count as 0.
+fn to_amount(value: i128) -> u128 {
+    (value.max(0) as u128) / FIXED_ONE
+}
+
+/// Reward pools of vaults (`VaultRewards`): one pool per collateral currency, where each vault
+/// stakes its collateral and fees are distributed by stake. Rewards of a vault are shared with its
+/// nominators on withdrawal, see [`NominationPallet`](super::nomination_pallet::NominationPallet).
+#[async_trait]
+pub trait RewardPallet: SubstrateApi {
+    /// total stake in the pool of `collateral`
+    async fn reward_total_stake(&self, collateral: CurrencyId) -> u128 {
+        let query = metadata::ggx::storage()
+            .vault_rewards()
+            .total_stake(collateral);
+        let stake = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get total stake");
+        to_amount(stake.0)
+    }
+
+    /// total rewards in `currency` distributed over all pools
+    async fn reward_total_rewards(&self, currency: CurrencyId) -> u128 {
+        let query = metadata::ggx::storage()
+            .vault_rewards()
+            .total_rewards(currency);
+        let rewards = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get total rewards");
+        to_amount(rewards.0)
+    }
+
+    /// reward currencies of the pool of `collateral`
+    async fn reward_currencies(&self, collateral: CurrencyId) -> Vec<CurrencyId> {
+        let query = metadata::ggx::storage()
+            .vault_rewards()
+            .reward_currencies(collateral);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get reward currencies")
+            .map(|currencies| currencies.0.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// stake of `vault_id` in the pool of its collateral
+    async fn reward_vault_stake(&self, vault_id: VaultId<AccountId32, CurrencyId>) -> u128 {
+        let query = metadata::ggx::storage()
+            .vault_rewards()
+            .stake((vault_id.currencies.collateral.clone(), vault_id));
+        let stake = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get vault stake");
+        to_amount(stake.0)
+    }
+
+    /// rewards in `currency` of `vault_id` not withdrawn from the pool yet
+    async fn reward_vault_reward(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        currency: CurrencyId,
+    ) -> u128 {
+        let storage = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest");
+        let rewards = metadata::ggx::storage().vault_rewards();
+        let pool = vault_id.currencies.collateral.clone();
+        let stake = storage
+            .fetch_or_default(&rewards.stake((pool.clone(), vault_id.clone())))
+            .await
+            .expect("cannot get vault stake");
+        let reward_per_token = storage
+            .fetch_or_default(&rewards.reward_per_token(currency.clone(), pool.clone()))
+            .await
+            .expect("cannot get reward per token");
+        let tally = storage
+            .fetch_or_default(&rewards.reward_tally(currency, (pool, vault_id)))
+            .await
+            .expect("cannot get reward tally");
+        to_amount(fixed_mul(stake.0, reward_per_token.0) - tally.0)
+    }
+}
+
+#[async_trait]
+impl RewardPallet for GgxNodeContainer {}
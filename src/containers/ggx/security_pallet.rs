@@ -0,0 +1,48 @@
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use async_trait::async_trait;
+
+type RuntimeCall = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+type SecurityCall = metadata::ggx::runtime_types::security::pallet::Call;
+
+/// The `security` pallet of this runtime has no Running/Error/Shutdown status, only a counter of
+/// active blocks that sudo can stop. Periods of issue, redeem and replace requests are counted in
+/// active blocks, so a deactivated counter freezes their expiry.
+#[async_trait]
+pub trait SecurityPallet: SubstrateApi {
+    async fn security_is_deactivated(&self) -> bool {
+        let query = metadata::ggx::storage().security().is_deactivated();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get security deactivated")
+    }
+
+    /// number of blocks the counter was active
+    async fn security_active_block_count(&self) -> u32 {
+        let query = metadata::ggx::storage().security().active_block_count();
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch_or_default(&query)
+            .await
+            .expect("cannot get active block count")
+    }
+
+    async fn security_set_active(&self, is_active: bool) {
+        log::info!("GGX: Setting active block counter to {}", is_active);
+        self.sudo(RuntimeCall::Security(SecurityCall::activate_counter {
+            is_active,
+        }))
+        .await;
+    }
+}
+
+#[async_trait]
+impl SecurityPallet for GgxNodeContainer {}
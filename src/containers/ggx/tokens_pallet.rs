@@ -0,0 +1,184 @@
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::CurrencyId;
+use crate::metadata::ggx::runtime_types::orml_tokens::AccountData;
+use crate::wait::{wait_until, DEFAULT_POLL_INTERVAL};
+use async_trait::async_trait;
+use std::time::Duration;
+use subxt::utils::{AccountId32, MultiAddress};
+use subxt_signer::sr25519::Keypair;
+
+type RuntimeCall = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+type TokensCall = metadata::ggx::runtime_types::orml_tokens::module::Call;
+
+/// Multi-currency balances of `orml_tokens`. All methods take any [`CurrencyId`], e.g.
+/// `CurrencyId::Token(TokenSymbol::KBTC)` or `CurrencyId::ForeignAsset(id)`.
+///
+/// GGXT, the collateral of the dev vaults, is an `orml_tokens` currency like the others. The
+/// native token of GGX is kept by `pallet_balances` and has no [`CurrencyId`], while the native
+/// currency of the interbtc pallets ([`tokens_native_currency`](Self::tokens_native_currency))
+/// is KINT.
+#[async_trait]
+pub trait TokensPallet: SubstrateApi {
+    /// `GetNativeCurrencyId` of the currency pallet
+    fn tokens_native_currency(&self) -> CurrencyId {
+        let query = metadata::ggx::constants()
+            .currency()
+            .get_native_currency_id();
+        self.api()
+            .constants()
+            .at(&query)
+            .expect("cannot get native currency id")
+    }
+
+    /// balances of `account`, all zero if it holds none of `currency`
+    async fn tokens_account(
+        &self,
+        account: AccountId32,
+        currency: CurrencyId,
+    ) -> AccountData<u128> {
+        let query = metadata::ggx::storage()
+            .tokens()
+            .accounts(account, currency);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get token balance")
+            .unwrap_or(AccountData {
+                free: 0,
+                reserved: 0,
+                frozen: 0,
+            })
+    }
+
+    async fn tokens_free_balance(&self, account: AccountId32, currency: CurrencyId) -> u128 {
+        self.tokens_account(account, currency).await.free
+    }
+
+    async fn tokens_reserved_balance(&self, account: AccountId32, currency: CurrencyId) -> u128 {
+        self.tokens_account(account, currency).await.reserved
+    }
+
+    async fn tokens_frozen_balance(&self, account: AccountId32, currency: CurrencyId) -> u128 {
+        self.tokens_account(account, currency).await.frozen
+    }
+
+    async fn tokens_total_issuance(&self, currency: CurrencyId) -> u128 {
+        let query = metadata::ggx::storage().tokens().total_issuance(currency);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get total issuance")
+            .unwrap_or_default()
+    }
+
+    async fn tokens_transfer(
+        &self,
+        from: Keypair,
+        to: AccountId32,
+        currency: CurrencyId,
+        amount: u128,
+    ) {
+        log::info!("GGX: Transferring {} of {:?} to {}", amount, currency, to);
+        let tx = metadata::ggx::tx()
+            .tokens()
+            .transfer(MultiAddress::Id(to), currency, amount);
+        self.send_tx_and_wait_until_finalized(from, tx).await;
+    }
+
+    /// transfer all free balance, `keep_alive` leaves the existential deposit
+    async fn tokens_transfer_all(
+        &self,
+        from: Keypair,
+        to: AccountId32,
+        currency: CurrencyId,
+        keep_alive: bool,
+    ) {
+        log::info!("GGX: Transferring all {:?} to {}", currency, to);
+        let tx =
+            metadata::ggx::tx()
+                .tokens()
+                .transfer_all(MultiAddress::Id(to), currency, keep_alive);
+        self.send_tx_and_wait_until_finalized(from, tx).await;
+    }
+
+    /// Set balances of `who` with sudo, total issuance is adjusted accordingly.
+    async fn tokens_set_balance(
+        &self,
+        who: AccountId32,
+        currency: CurrencyId,
+        free: u128,
+        reserved: u128,
+    ) {
+        log::info!("GGX: Setting {:?} balance of {} to {}", currency, who, free);
+        self.sudo(RuntimeCall::Tokens(TokensCall::set_balance {
+            who: MultiAddress::Id(who),
+            currency_id: currency,
+            new_free: free,
+            new_reserved: reserved,
+        }))
+        .await;
+    }
+
+    /// poll free balance of `account` until `predicate` accepts it
+    async fn tokens_wait_for_balance<P>(
+        &self,
+        account: AccountId32,
+        currency: CurrencyId,
+        predicate: P,
+        timeout: Duration,
+    ) -> u128
+    where
+        P: Fn(u128) -> bool + Send + Sync,
+    {
+        wait_until(
+            timeout,
+            DEFAULT_POLL_INTERVAL,
+            || self.tokens_free_balance(account.clone(), currency.clone()),
+            |balance| predicate(*balance),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl TokensPallet for GgxNodeContainer {}
+
+#[cfg(test)]
+mod tests {
+    use subxt::ext::codec::{Decode, Encode};
+    use subxt::Metadata;
+
+    use super::*;
+    use crate::metadata::ggx::runtime_types::interbtc_primitives::TokenSymbol;
+
+    /// GGXT balances are in orml_tokens, as the native currency of the runtime is KINT
+    #[test]
+    fn test_ggxt_is_not_native() {
+        let bytes = include_bytes!("../../metadata/metadata_ggx_brooklyn.scale");
+        let metadata = Metadata::decode(&mut &bytes[..]).expect("cannot decode metadata");
+        let value = metadata
+            .pallet_by_name("Currency")
+            .and_then(|p| p.constant_by_name("GetNativeCurrencyId"))
+            .expect("no GetNativeCurrencyId constant")
+            .value();
+        let native = CurrencyId::decode(&mut &value[..]).unwrap();
+
+        assert_eq!(
+            native.encode(),
+            CurrencyId::Token(TokenSymbol::KINT).encode()
+        );
+        assert_ne!(
+            native.encode(),
+            CurrencyId::Token(TokenSymbol::GGXT).encode()
+        );
+    }
+}
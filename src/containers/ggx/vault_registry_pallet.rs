@@ -0,0 +1,362 @@
+use crate::containers::ggx::oracle_pallet::OraclePallet;
+use crate::containers::ggx::{GgxNodeContainer, SubstrateApi};
+use crate::metadata;
+use crate::metadata::ggx::runtime_types::bitcoin::address::PublicKey;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{
+    CurrencyId, VaultCurrencyPair, VaultId,
+};
+use crate::metadata::ggx::runtime_types::sp_arithmetic::fixed_point::FixedU128;
+use crate::metadata::ggx::runtime_types::vault_registry::types::{Vault, VaultStatus};
+use crate::metadata::ggx::vault_registry::events::RegisterVault;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+use async_trait::async_trait;
+use bitcoincore_rpc::bitcoin::PublicKey as BtcPublicKey;
+use std::time::Duration;
+use subxt::utils::AccountId32;
+use subxt_signer::sr25519::Keypair;
+
+/// 1.0 in FixedU128/FixedI128
+pub const FIXED_ONE: u128 = 1_000_000_000_000_000_000;
+
+pub type VaultInfo = Vault<AccountId32, u32, u128, CurrencyId, FixedU128>;
+
+/// `amount` / `fixed`
+fn fixed_div(amount: u128, fixed: &FixedU128) -> u128 {
+    amount
+        .checked_mul(FIXED_ONE)
+        .expect("amount is too large")
+        .checked_div(fixed.0)
+        .expect("division by zero")
+}
+
+/// `a` * `b` of two signed fixed point values, e.g. stake and reward per token, without
+/// overflowing on large stakes
+pub(crate) fn fixed_mul(a: i128, b: i128) -> i128 {
+    let one = FIXED_ONE as i128;
+    (a / one) * b + (a % one) * b / one
+}
+
+#[async_trait]
+pub trait VaultRegistryPallet: SubstrateApi + OraclePallet {
+    /// Register the BTC public key of `vault`, required once per account before
+    /// [`vault_registry_register_vault`](Self::vault_registry_register_vault).
+    async fn vault_registry_register_public_key(&self, vault: Keypair, public_key: &BtcPublicKey) {
+        let tx = metadata::ggx::tx()
+            .vault_registry()
+            .register_public_key(PublicKey(public_key.inner.serialize()));
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+
+    async fn vault_registry_register_vault(
+        &self,
+        vault: Keypair,
+        currencies: VaultCurrencyPair<CurrencyId>,
+        collateral: u128,
+    ) -> RegisterVault {
+        log::info!(
+            "GGX: Registering vault {:?} with collateral {}",
+            currencies,
+            collateral
+        );
+        let tx = metadata::ggx::tx()
+            .vault_registry()
+            .register_vault(currencies, collateral);
+        self.send_tx_and_get_events(vault, tx)
+            .await
+            .find_first::<RegisterVault>()
+            .expect("cannot decode RegisterVault event")
+            .expect("no RegisterVault event")
+    }
+
+    async fn vault_registry_deposit_collateral(
+        &self,
+        vault: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) {
+        log::info!("GGX: Depositing {} collateral to {:?}", amount, vault_id);
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .deposit_collateral(vault_id, amount);
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+
+    async fn vault_registry_withdraw_collateral(
+        &self,
+        vault: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        amount: u128,
+    ) {
+        log::info!("GGX: Withdrawing {} collateral from {:?}", amount, vault_id);
+        let tx = metadata::ggx::tx()
+            .nomination()
+            .withdraw_collateral(vault_id, Some(amount), None);
+        self.send_tx_and_wait_until_finalized(vault, tx).await;
+    }
+
+    async fn vault_registry_get_vault(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> Option<VaultInfo> {
+        let query = metadata::ggx::storage().vault_registry().vaults(vault_id);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get vault")
+    }
+
+    async fn vault_registry_get_vaults(&self) -> Vec<VaultInfo> {
+        let query = metadata::ggx::storage().vault_registry().vaults_root();
+
+        let mut it = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .iter(query, 100)
+            .await
+            .expect("cannot iter");
+
+        let mut vaults = vec![];
+        while let Ok(Some(v)) = it.next().await {
+            vaults.push(v.1);
+        }
+        vaults
+    }
+
+    /// Wait until `vault_id` is registered, e.g. by a vault client with `--auto-register`.
+    async fn vault_registry_wait_for_vault(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+        timeout: Duration,
+    ) -> VaultInfo {
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            self.vault_registry_get_vault(vault_id.clone())
+                .await
+                .ok_or("vault is not registered")
+        })
+        .await
+    }
+
+    async fn vault_registry_is_liquidated(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> bool {
+        let vault = self
+            .vault_registry_get_vault(vault_id)
+            .await
+            .expect("vault is not found");
+        matches!(vault.status, VaultStatus::Liquidated)
+    }
+
+    /// Liquidate `vault_id` if it is below the liquidation threshold, e.g. after
+    /// [`FakeOracle`](crate::containers::ggx::oracle_pallet::FakeOracle) raised the BTC price.
+    async fn vault_registry_report_undercollateralized_vault(
+        &self,
+        reporter: Keypair,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) {
+        log::info!("GGX: Reporting undercollateralized vault {:?}", vault_id);
+        let tx = metadata::ggx::tx()
+            .vault_registry()
+            .report_undercollateralized_vault(vault_id);
+        self.send_tx_and_wait_until_finalized(reporter, tx).await;
+    }
+
+    /// collateral backing `vault_id`, including nominated collateral
+    async fn vault_registry_collateral(&self, vault_id: VaultId<AccountId32, CurrencyId>) -> u128 {
+        let storage = self
+            .api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest");
+        let nonce = storage
+            .fetch_or_default(
+                &metadata::ggx::storage()
+                    .vault_staking()
+                    .nonce(vault_id.clone()),
+            )
+            .await
+            .expect("cannot get vault staking nonce");
+        let stake = storage
+            .fetch_or_default(
+                &metadata::ggx::storage()
+                    .vault_staking()
+                    .total_current_stake(nonce, vault_id),
+            )
+            .await
+            .expect("cannot get vault stake");
+        (stake.0.max(0) as u128) / FIXED_ONE
+    }
+
+    /// custom secure threshold of the vault or the default of its currency pair
+    async fn vault_registry_secure_threshold(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> FixedU128 {
+        let vault = self
+            .vault_registry_get_vault(vault_id.clone())
+            .await
+            .expect("vault is not found");
+        if let Some(threshold) = vault.secure_collateral_threshold {
+            return threshold;
+        }
+
+        let query = metadata::ggx::storage()
+            .vault_registry()
+            .secure_collateral_threshold(vault_id.currencies);
+        self.api()
+            .storage()
+            .at_latest()
+            .await
+            .expect("cannot get storage at latest")
+            .fetch(&query)
+            .await
+            .expect("cannot get secure collateral threshold")
+            .expect("secure collateral threshold is not set")
+    }
+
+    /// KBTC that can still be issued with `vault_id` at its secure threshold
+    async fn vault_registry_issuable_tokens(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> u128 {
+        let vault = self
+            .vault_registry_get_vault(vault_id.clone())
+            .await
+            .expect("vault is not found");
+        let collateral = self.vault_registry_collateral(vault_id.clone()).await;
+        let threshold = self.vault_registry_secure_threshold(vault_id.clone()).await;
+        let collateral_currency = vault_id.currencies.collateral;
+        let rate = self
+            .oracle_exchange_rate(collateral_currency.clone())
+            .await
+            .unwrap_or_else(|| panic!("no exchange rate for {collateral_currency:?}"));
+
+        let max_tokens = fixed_div(fixed_div(collateral, &threshold), &rate);
+        max_tokens.saturating_sub(vault.issued_tokens + vault.to_be_issued_tokens)
+    }
+
+    /// KBTC that can be redeemed from `vault_id`
+    async fn vault_registry_redeemable_tokens(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> u128 {
+        let vault = self
+            .vault_registry_get_vault(vault_id)
+            .await
+            .expect("vault is not found");
+        vault
+            .issued_tokens
+            .saturating_sub(vault.to_be_redeemed_tokens)
+    }
+
+    /// collateral / issued tokens valued in collateral, e.g. 1.5 for 150%.
+    /// `None` if the vault has not issued anything.
+    async fn vault_registry_collateralization(
+        &self,
+        vault_id: VaultId<AccountId32, CurrencyId>,
+    ) -> Option<f64> {
+        let vault = self
+            .vault_registry_get_vault(vault_id.clone())
+            .await
+            .expect("vault is not found");
+        if vault.issued_tokens == 0 {
+            return None;
+        }
+
+        let collateral = self.vault_registry_collateral(vault_id.clone()).await;
+        let collateral_currency = vault_id.currencies.collateral;
+        let rate = self
+            .oracle_exchange_rate(collateral_currency.clone())
+            .await
+            .unwrap_or_else(|| panic!("no exchange rate for {collateral_currency:?}"));
+        let issued = vault.issued_tokens as f64 * rate.0 as f64 / FIXED_ONE as f64;
+        Some(collateral as f64 / issued)
+    }
+
+    /// Set minimum collateral and ceiling of a new collateral currency with sudo.
+    async fn vault_registry_set_collateral_limits(
+        &self,
+        currencies: VaultCurrencyPair<CurrencyId>,
+        minimum: u128,
+        ceiling: u128,
+    ) {
+        type Call = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+        type VaultRegistryCall = metadata::ggx::runtime_types::vault_registry::pallet::Call;
+
+        let calls = [
+            VaultRegistryCall::set_minimum_collateral {
+                currency_id: currencies.collateral.clone(),
+                minimum,
+            },
+            VaultRegistryCall::set_system_collateral_ceiling {
+                currency_pair: currencies,
+                ceiling,
+            },
+        ];
+        for call in calls {
+            self.sudo(Call::VaultRegistry(call)).await;
+        }
+    }
+
+    /// Set secure, premium redeem and liquidation thresholds of a currency pair with sudo,
+    /// e.g. to let a new collateral currency be used by vaults.
+    async fn vault_registry_set_thresholds(
+        &self,
+        currencies: VaultCurrencyPair<CurrencyId>,
+        secure: FixedU128,
+        premium: FixedU128,
+        liquidation: FixedU128,
+    ) {
+        type Call = metadata::ggx::runtime_types::ggxchain_runtime_brooklyn::RuntimeCall;
+        type VaultRegistryCall = metadata::ggx::runtime_types::vault_registry::pallet::Call;
+
+        let calls = [
+            VaultRegistryCall::set_secure_collateral_threshold {
+                currency_pair: currencies.clone(),
+                threshold: secure,
+            },
+            VaultRegistryCall::set_premium_redeem_threshold {
+                currency_pair: currencies.clone(),
+                threshold: premium,
+            },
+            VaultRegistryCall::set_liquidation_collateral_threshold {
+                currency_pair: currencies,
+                threshold: liquidation,
+            },
+        ];
+        for call in calls {
+            self.sudo(Call::VaultRegistry(call)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl VaultRegistryPallet for GgxNodeContainer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_div() {
+        assert_eq!(fixed_div(300, &FixedU128(FIXED_ONE * 3 / 2)), 200);
+        assert_eq!(fixed_div(300, &FixedU128(FIXED_ONE)), 300);
+    }
+
+    #[test]
+    fn test_fixed_mul() {
+        let one = FIXED_ONE as i128;
+        assert_eq!(fixed_mul(3 * one, one / 2), 3 * one / 2);
+        // 500M collateral staked, 2e-6 reward per token: 1000 of reward
+        let stake = 500_000_000 * one;
+        assert_eq!(fixed_mul(stake, 2 * one / 1_000_000), 1000 * one);
+    }
+}
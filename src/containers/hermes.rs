@@ -3,6 +3,7 @@ use std::time::Duration;
 use testcontainers::core::{CmdWaitFor, ExecCommand, Image, WaitFor};
 use testcontainers::{ContainerAsync, ImageArgs};
 
+use crate::logs::ContainerLogs;
 use crate::vecs;
 
 #[derive(Debug, Eq, Clone, PartialEq)]
@@ -48,9 +49,36 @@ impl Image for HermesImage {
     }
 }
 
-pub struct HermesContainer(pub ContainerAsync<HermesImage>);
+pub struct HermesContainer(ContainerAsync<HermesImage>);
+
+impl ContainerLogs for HermesContainer {
+    fn container_id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn log_name(&self) -> &str {
+        "hermes"
+    }
+}
+
+impl Drop for HermesContainer {
+    fn drop(&mut self) {
+        crate::logs::forget(self.container_id());
+    }
+}
 
 impl HermesContainer {
+    /// wraps the container and starts capturing its logs
+    pub fn from(container: ContainerAsync<HermesImage>) -> Self {
+        let result = Self(container);
+        result.logs();
+        result
+    }
+
+    pub fn container(&self) -> &ContainerAsync<HermesImage> {
+        &self.0
+    }
+
     pub async fn exec(&self, cmd: Vec<String>, wait_for: CmdWaitFor, timeout: Duration) {
         tokio::time::timeout(timeout, async {
             let c = ExecCommand::new(cmd).with_cmd_ready_condition(wait_for);
@@ -74,7 +102,7 @@ mod tests {
     #[tokio::test]
     async fn test_hermes() {
         let container = HermesImage::default().start().await;
-        let container = HermesContainer(container);
+        let container = HermesContainer::from(container);
         let cmd = vecs![
             "hermes",
             "--config",
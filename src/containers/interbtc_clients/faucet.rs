@@ -0,0 +1,326 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use subxt::ext::codec::Encode;
+use subxt::utils::AccountId32;
+use testcontainers::runners::AsyncRunner;
+use tokio::net::TcpStream;
+
+use super::{dev_keypair, InterbtcClientsContainer, InterbtcClientsImage};
+use crate::containers::ggx::tokens_pallet::TokensPallet;
+use crate::containers::ggx::GgxNodeContainer;
+use crate::images;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::CurrencyId;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+
+/// default port of the faucet JSON-RPC server on the host
+pub const HTTP_PORT: u16 = 3033;
+
+/// where [`FaucetConfig::with_allowance_config_file`] is mounted in the container
+pub const ALLOWANCE_CONFIG_PATH: &str = "/faucet-allowance-config.json";
+
+/// a started faucet must serve HTTP within this time
+pub const FAUCET_READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Command line of a faucet. Defaults to dev account `alice` serving on [`HTTP_PORT`],
+/// connected to GGX on its default port of the host.
+#[derive(Debug, Clone)]
+pub struct FaucetConfig {
+    keyring: String,
+    http_port: u16,
+    allowance_config_file: Option<String>,
+    parachain_url: String,
+    extra: Vec<String>,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        Self::new("alice")
+    }
+}
+
+impl FaucetConfig {
+    /// faucet paying from dev account `keyring`
+    pub fn new(keyring: &str) -> Self {
+        Self {
+            keyring: keyring.to_string(),
+            http_port: HTTP_PORT,
+            allowance_config_file: None,
+            parachain_url: "ws://127.0.0.1:9944".to_string(),
+            extra: vec![],
+        }
+    }
+
+    pub fn with_http_port(mut self, port: u16) -> Self {
+        self.http_port = port;
+        self
+    }
+
+    /// host path of the allowance config (amounts per user and vault), mounted to
+    /// [`ALLOWANCE_CONFIG_PATH`]
+    pub fn with_allowance_config_file(mut self, path: &str) -> Self {
+        self.allowance_config_file = Some(path.to_string());
+        self
+    }
+
+    /// GGX websocket url
+    pub fn with_parachain_url(mut self, url: &str) -> Self {
+        self.parachain_url = url.to_string();
+        self
+    }
+
+    /// any other faucet flag
+    pub fn with_arg(mut self, arg: &str) -> Self {
+        self.extra.push(arg.to_string());
+        self
+    }
+
+    pub fn keyring(&self) -> &str {
+        &self.keyring
+    }
+
+    pub fn http_port(&self) -> u16 {
+        self.http_port
+    }
+
+    pub fn allowance_config_file(&self) -> Option<&str> {
+        self.allowance_config_file.as_deref()
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        dev_keypair(&self.keyring).public_key().to_account_id()
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "faucet".to_string(),
+            format!("--btc-parachain-url={}", self.parachain_url),
+            format!("--keyring={}", self.keyring),
+            format!("--http-addr=0.0.0.0:{}", self.http_port),
+        ];
+        if self.allowance_config_file.is_some() {
+            args.push(format!("--allowance-config={ALLOWANCE_CONFIG_PATH}"));
+        }
+        args.extend(self.extra.iter().cloned());
+        args
+    }
+}
+
+pub struct FaucetContainer {
+    inner: InterbtcClientsContainer,
+    cfg: FaucetConfig,
+}
+
+impl FaucetContainer {
+    pub fn from(container: InterbtcClientsContainer, cfg: FaucetConfig) -> Self {
+        Self {
+            inner: container,
+            cfg,
+        }
+    }
+
+    pub fn container(&self) -> &InterbtcClientsContainer {
+        &self.inner
+    }
+
+    pub fn config(&self) -> &FaucetConfig {
+        &self.cfg
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        self.cfg.account_id()
+    }
+
+    pub fn get_http_port(&self) -> u16 {
+        self.cfg.http_port()
+    }
+
+    /// JSON-RPC url on the host
+    pub fn get_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.get_http_port())
+    }
+
+    pub fn client(&self) -> FaucetClient {
+        FaucetClient::new(&self.get_url())
+    }
+
+    /// Wait until the faucet accepts connections.
+    pub async fn wait_until_ready(&self, timeout: Duration) {
+        let addr = format!("127.0.0.1:{}", self.get_http_port());
+        eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+            TcpStream::connect(&addr).await.map(|_| ())
+        })
+        .await
+    }
+}
+
+/// Start a faucet described by `cfg` on the host network, connected to `ggx`, and wait until it
+/// serves HTTP. Its account must hold the funds it hands out.
+pub async fn start_faucet(ggx: &GgxNodeContainer, cfg: &FaucetConfig) -> FaucetContainer {
+    log::info!("Starting Faucet {}", cfg.keyring());
+
+    let cfg = cfg.clone().with_parachain_url(&ggx.get_host_ws_url().await);
+
+    images::require(&InterbtcClientsImage::brooklyn()).await;
+    let container = InterbtcClientsImage::faucet(&cfg).start().await;
+    let faucet = FaucetContainer::from(InterbtcClientsContainer::from(container), cfg);
+    faucet.wait_until_ready(FAUCET_READY_TIMEOUT).await;
+    faucet
+}
+
+/// The faucet takes a single hex string param: SCALE encoded `(account_id, currency_id)`.
+fn fund_account_params(account: &AccountId32, currency: &CurrencyId) -> Value {
+    json!([format!("0x{}", hex::encode((account, currency).encode()))])
+}
+
+/// Client of the faucet JSON-RPC API.
+#[derive(Debug, Clone)]
+pub struct FaucetClient {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl FaucetClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response: Value = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+        match response.get("error") {
+            Some(error) => Err(anyhow::anyhow!("{method} failed: {error}")),
+            None => Ok(response["result"].clone()),
+        }
+    }
+
+    /// Request the user allowance of `currency` for `account`, at most once per faucet period.
+    pub async fn user_fund_account(
+        &self,
+        account: &AccountId32,
+        currency: &CurrencyId,
+    ) -> anyhow::Result<()> {
+        self.call("user_fund_account", fund_account_params(account, currency))
+            .await
+            .map(|_| ())
+    }
+
+    /// Request the vault allowance of `currency` for `account`.
+    pub async fn vault_fund_account(
+        &self,
+        account: &AccountId32,
+        currency: &CurrencyId,
+    ) -> anyhow::Result<()> {
+        self.call("vault_fund_account", fund_account_params(account, currency))
+            .await
+            .map(|_| ())
+    }
+
+    /// Fund `account` as a user and wait until its `orml_tokens` balance of `currency` grows,
+    /// returns the received amount.
+    pub async fn fund_user(
+        &self,
+        ggx: &GgxNodeContainer,
+        account: &AccountId32,
+        currency: &CurrencyId,
+        timeout: Duration,
+    ) -> u128 {
+        let before = ggx
+            .tokens_free_balance(account.clone(), currency.clone())
+            .await;
+        self.user_fund_account(account, currency)
+            .await
+            .expect("cannot fund user account");
+        wait_for_funds(ggx, account, currency, before, timeout).await
+    }
+
+    /// Fund `account` as a vault and wait until its `orml_tokens` balance of `currency` grows,
+    /// returns the received amount.
+    pub async fn fund_vault(
+        &self,
+        ggx: &GgxNodeContainer,
+        account: &AccountId32,
+        currency: &CurrencyId,
+        timeout: Duration,
+    ) -> u128 {
+        let before = ggx
+            .tokens_free_balance(account.clone(), currency.clone())
+            .await;
+        self.vault_fund_account(account, currency)
+            .await
+            .expect("cannot fund vault account");
+        wait_for_funds(ggx, account, currency, before, timeout).await
+    }
+}
+
+async fn wait_for_funds(
+    ggx: &GgxNodeContainer,
+    account: &AccountId32,
+    currency: &CurrencyId,
+    before: u128,
+    timeout: Duration,
+) -> u128 {
+    let after = ggx
+        .tokens_wait_for_balance(
+            account.clone(),
+            currency.clone(),
+            |after| after > before,
+            timeout,
+        )
+        .await;
+    let received = after - before;
+    log::info!(
+        "Faucet: {} received {} of {:?}",
+        account,
+        received,
+        currency
+    );
+    received
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faucet_args() {
+        let args = FaucetConfig::default()
+            .with_http_port(3034)
+            .with_allowance_config_file("/tmp/allowance.json")
+            .to_args();
+        assert_eq!(args[0], "faucet");
+        assert!(args.contains(&"--keyring=alice".to_string()));
+        assert!(args.contains(&"--http-addr=0.0.0.0:3034".to_string()));
+        assert!(args.contains(&format!("--allowance-config={ALLOWANCE_CONFIG_PATH}")));
+
+        let args = FaucetConfig::new("bob").to_args();
+        assert!(args.contains(&format!("--http-addr=0.0.0.0:{HTTP_PORT}")));
+        assert!(!args.iter().any(|a| a.starts_with("--allowance-config")));
+    }
+
+    #[test]
+    fn test_fund_account_params() {
+        use crate::metadata::ggx::runtime_types::interbtc_primitives::TokenSymbol;
+
+        let account = AccountId32([1; 32]);
+        let params = fund_account_params(&account, &CurrencyId::Token(TokenSymbol::GGXT));
+        // 32 bytes of account, then `Token` (variant 0) and `GGXT` (variant 148)
+        let expected = format!("0x{}0094", "01".repeat(32));
+        assert_eq!(params, json!([expected]));
+    }
+}
@@ -0,0 +1,204 @@
+//! interbtc-clients: vault, oracle and faucet, all run from the same image in different modes.
+
+pub mod faucet;
+pub mod oracle;
+pub mod vault;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use subxt_signer::sr25519::{dev, Keypair};
+use testcontainers::core::WaitFor;
+use testcontainers::{ContainerAsync, Image, RunnableImage};
+
+use crate::logs::ContainerLogs;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{CurrencyId, TokenSymbol};
+
+pub use faucet::{start_faucet, FaucetClient, FaucetConfig, FaucetContainer};
+pub use oracle::{start_oracle, OracleConfig, OracleContainer};
+pub use vault::{start_vault, start_vaults, VaultConfig, VaultContainer};
+
+#[derive(Clone, Debug, Default)]
+pub struct InterbtcClientsImage {
+    pub image: String,
+    pub tag: String,
+    /// replaces the default 2 seconds wait
+    pub wait_for: Vec<WaitFor>,
+}
+
+const DEFAULT_INTERBTC_CLIENTS_IMAGE: &str = "ggxdocker/interbtc-clients";
+
+pub enum InterbtcClientsNetwork {
+    Brooklyn,
+    Sydney,
+}
+
+impl InterbtcClientsNetwork {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            InterbtcClientsNetwork::Brooklyn => "brooklyn-9b1ac6fe790e6504ab4b06f7baedcd84958d364a",
+            InterbtcClientsNetwork::Sydney => "sydney-9b1ac6fe790e6504ab4b06f7baedcd84958d364a",
+        }
+    }
+}
+
+impl InterbtcClientsImage {
+    pub fn brooklyn() -> Self {
+        Self {
+            image: DEFAULT_INTERBTC_CLIENTS_IMAGE.to_string(),
+            tag: InterbtcClientsNetwork::Brooklyn.as_str().to_string(),
+            wait_for: vec![],
+        }
+    }
+
+    pub fn sydney() -> Self {
+        Self {
+            image: DEFAULT_INTERBTC_CLIENTS_IMAGE.to_string(),
+            tag: InterbtcClientsNetwork::Sydney.as_str().to_string(),
+            wait_for: vec![],
+        }
+    }
+
+    /// Vault on the host network, see [`start_vault`] which also waits until it is registered.
+    pub fn vault(cfg: &VaultConfig) -> RunnableImage<Self> {
+        Self::brooklyn().with_mode(cfg.to_args(), &format!("vault-{}", cfg.keyring()))
+    }
+
+    /// Oracle on the host network, see [`start_oracle`] which also waits for its first feed.
+    pub fn oracle(cfg: &OracleConfig) -> RunnableImage<Self> {
+        let image = Self::brooklyn().with_mode(cfg.to_args(), &format!("oracle-{}", cfg.keyring()));
+        match cfg.config_file() {
+            Some(path) => image.with_volume((path.to_string(), oracle::CONFIG_PATH.to_string())),
+            None => image,
+        }
+    }
+
+    /// Faucet on the host network, see [`start_faucet`] which also waits for its HTTP server.
+    pub fn faucet(cfg: &FaucetConfig) -> RunnableImage<Self> {
+        let image = Self::brooklyn().with_mode(cfg.to_args(), "faucet");
+        match cfg.allowance_config_file() {
+            Some(path) => {
+                image.with_volume((path.to_string(), faucet::ALLOWANCE_CONFIG_PATH.to_string()))
+            }
+            None => image,
+        }
+    }
+
+    fn with_mode(mut self, args: Vec<String>, name: &str) -> RunnableImage<Self> {
+        // readiness differs per mode and is checked on GGX or HTTP by the start_* functions
+        self.wait_for = vec![WaitFor::Nothing];
+        RunnableImage::from((self, args))
+            .with_network("host")
+            .with_container_name(unique_container_name(name))
+    }
+}
+
+impl Image for InterbtcClientsImage {
+    type Args = Vec<String>;
+
+    fn name(&self) -> String {
+        self.image.to_string()
+    }
+
+    fn tag(&self) -> String {
+        self.tag.clone()
+    }
+
+    fn ready_conditions(&self) -> Vec<WaitFor> {
+        if !self.wait_for.is_empty() {
+            return self.wait_for.clone();
+        }
+        vec![WaitFor::Duration {
+            // wait 2 seconds for the container to be ready
+            length: Duration::from_secs(2),
+            // NOTE: this single Image is used for oracle,faucet,vault so do not put WaitFor tool-specific logs here
+        }]
+    }
+}
+
+pub struct InterbtcClientsContainer(ContainerAsync<InterbtcClientsImage>);
+
+impl ContainerLogs for InterbtcClientsContainer {
+    fn container_id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn log_name(&self) -> &str {
+        "interbtc-clients"
+    }
+}
+
+impl Drop for InterbtcClientsContainer {
+    fn drop(&mut self) {
+        crate::logs::forget(self.container_id());
+    }
+}
+
+impl InterbtcClientsContainer {
+    /// wraps the container and starts capturing its logs
+    pub fn from(container: ContainerAsync<InterbtcClientsImage>) -> Self {
+        let result = Self(container);
+        result.logs();
+        result
+    }
+
+    pub fn container(&self) -> &ContainerAsync<InterbtcClientsImage> {
+        &self.0
+    }
+}
+
+/// Docker container names are global, so `name` gets a suffix unique to this process and call,
+/// e.g. `vault-alice-1234-0`. Tests of one binary run in parallel and may start the same client.
+fn unique_container_name(name: &str) -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}-{}", name, std::process::id(), n)
+}
+
+/// dev account of `--keyring`, e.g. "alice"
+pub fn dev_keypair(keyring: &str) -> Keypair {
+    match keyring {
+        "alice" => dev::alice(),
+        "bob" => dev::bob(),
+        "charlie" => dev::charlie(),
+        "dave" => dev::dave(),
+        "eve" => dev::eve(),
+        "ferdie" => dev::ferdie(),
+        _ => panic!("unknown keyring {keyring}"),
+    }
+}
+
+/// currency of a token symbol as used in interbtc-clients args, e.g. "KSM"
+pub fn token_currency(symbol: &str) -> CurrencyId {
+    let token = match symbol {
+        "DOT" => TokenSymbol::DOT,
+        "IBTC" => TokenSymbol::IBTC,
+        "INTR" => TokenSymbol::INTR,
+        "KSM" => TokenSymbol::KSM,
+        "KBTC" => TokenSymbol::KBTC,
+        "KINT" => TokenSymbol::KINT,
+        "GGXT" => TokenSymbol::GGXT,
+        _ => panic!("unknown token {symbol}"),
+    };
+    CurrencyId::Token(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_names_are_unique() {
+        let cfg = VaultConfig::default();
+        let a = InterbtcClientsImage::vault(&cfg)
+            .container_name()
+            .clone()
+            .unwrap();
+        let b = InterbtcClientsImage::vault(&cfg)
+            .container_name()
+            .clone()
+            .unwrap();
+        assert!(a.starts_with(&format!("vault-{}-", cfg.keyring())));
+        assert_ne!(a, b);
+    }
+}
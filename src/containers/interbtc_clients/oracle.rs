@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use subxt::utils::AccountId32;
+use testcontainers::runners::AsyncRunner;
+
+use super::{dev_keypair, token_currency, InterbtcClientsContainer, InterbtcClientsImage};
+use crate::containers::ggx::oracle_pallet::OraclePallet;
+use crate::containers::ggx::GgxNodeContainer;
+use crate::images;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::oracle::Key;
+use crate::wait::{eventually, DEFAULT_POLL_INTERVAL};
+
+/// where [`OracleConfig::with_config_file`] is mounted in the container
+pub const CONFIG_PATH: &str = "/oracle-config.json";
+
+/// a started oracle must feed all its currencies within this time
+pub const ORACLE_READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Command line of an oracle. Defaults to dev account `bob`, connected to GGX on its default
+/// port of the host.
+#[derive(Debug, Clone)]
+pub struct OracleConfig {
+    keyring: String,
+    /// token symbols whose exchange rates are fed, e.g. "GGXT"
+    currencies: Vec<String>,
+    interval: Duration,
+    config_file: Option<String>,
+    parachain_url: String,
+    extra: Vec<String>,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self::new("bob")
+    }
+}
+
+impl OracleConfig {
+    /// oracle of dev account `keyring`, it must be an authorized oracle
+    pub fn new(keyring: &str) -> Self {
+        Self {
+            keyring: keyring.to_string(),
+            currencies: vec![],
+            interval: Duration::from_secs(5),
+            config_file: None,
+            parachain_url: "ws://127.0.0.1:9944".to_string(),
+            extra: vec![],
+        }
+    }
+
+    /// Expect exchange rates of `currency` (token symbol, e.g. "KSM") to be fed, the oracle is
+    /// ready once it has fed all of them. Prices themselves come from the oracle config file.
+    pub fn with_currency(mut self, currency: &str) -> Self {
+        self.currencies.push(currency.to_string());
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// host path of the oracle config (currencies and price feeds), mounted to [`CONFIG_PATH`]
+    pub fn with_config_file(mut self, path: &str) -> Self {
+        self.config_file = Some(path.to_string());
+        self
+    }
+
+    /// GGX websocket url
+    pub fn with_parachain_url(mut self, url: &str) -> Self {
+        self.parachain_url = url.to_string();
+        self
+    }
+
+    /// any other oracle flag
+    pub fn with_arg(mut self, arg: &str) -> Self {
+        self.extra.push(arg.to_string());
+        self
+    }
+
+    pub fn keyring(&self) -> &str {
+        &self.keyring
+    }
+
+    pub fn config_file(&self) -> Option<&str> {
+        self.config_file.as_deref()
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        dev_keypair(&self.keyring).public_key().to_account_id()
+    }
+
+    /// oracle keys fed by this oracle
+    pub fn keys(&self) -> Vec<Key> {
+        self.currencies
+            .iter()
+            .map(|c| Key::ExchangeRate(token_currency(c)))
+            .collect()
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "oracle".to_string(),
+            format!("--btc-parachain-url={}", self.parachain_url),
+            format!("--keyring={}", self.keyring),
+            format!("--interval={}", self.interval.as_millis()),
+        ];
+        if self.config_file.is_some() {
+            args.push(format!("--oracle-config={CONFIG_PATH}"));
+        }
+        args.extend(self.extra.iter().cloned());
+        args
+    }
+}
+
+pub struct OracleContainer {
+    inner: InterbtcClientsContainer,
+    cfg: OracleConfig,
+}
+
+impl OracleContainer {
+    pub fn from(container: InterbtcClientsContainer, cfg: OracleConfig) -> Self {
+        Self {
+            inner: container,
+            cfg,
+        }
+    }
+
+    pub fn container(&self) -> &InterbtcClientsContainer {
+        &self.inner
+    }
+
+    pub fn config(&self) -> &OracleConfig {
+        &self.cfg
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        self.cfg.account_id()
+    }
+
+    /// Wait until the oracle has fed all its currencies to `ggx`.
+    pub async fn wait_until_ready(&self, ggx: &GgxNodeContainer, timeout: Duration) {
+        for key in self.cfg.keys() {
+            eventually(timeout, DEFAULT_POLL_INTERVAL, || async {
+                ggx.oracle_raw_value(key.clone(), self.account_id())
+                    .await
+                    .ok_or_else(|| format!("{key:?} is not fed yet"))
+            })
+            .await;
+        }
+    }
+}
+
+/// Start an oracle described by `cfg` on the host network, connected to `ggx`, and wait until
+/// it has fed its currencies. Its account must be an authorized oracle, see
+/// [`OraclePallet::oracle_insert_authorized_oracle`].
+pub async fn start_oracle(ggx: &GgxNodeContainer, cfg: &OracleConfig) -> OracleContainer {
+    log::info!("Starting Oracle {}", cfg.keyring());
+
+    let cfg = cfg.clone().with_parachain_url(&ggx.get_host_ws_url().await);
+
+    images::require(&InterbtcClientsImage::brooklyn()).await;
+    let container = InterbtcClientsImage::oracle(&cfg).start().await;
+    let oracle = OracleContainer::from(InterbtcClientsContainer::from(container), cfg);
+    oracle.wait_until_ready(ggx, ORACLE_READY_TIMEOUT).await;
+    oracle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle_args() {
+        let cfg = OracleConfig::new("charlie")
+            .with_currency("GGXT")
+            .with_interval(Duration::from_secs(2))
+            .with_config_file("/tmp/oracle.json");
+        let args = cfg.to_args();
+        assert_eq!(args[0], "oracle");
+        assert!(args.contains(&"--keyring=charlie".to_string()));
+        assert!(args.contains(&"--interval=2000".to_string()));
+        assert!(args.contains(&format!("--oracle-config={CONFIG_PATH}")));
+        assert_eq!(cfg.keys().len(), 1);
+
+        let args = OracleConfig::default().to_args();
+        assert!(!args.iter().any(|a| a.starts_with("--oracle-config")));
+    }
+}
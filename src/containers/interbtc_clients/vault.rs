@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use subxt::utils::AccountId32;
+use testcontainers::runners::AsyncRunner;
+
+use super::{dev_keypair, token_currency, InterbtcClientsContainer, InterbtcClientsImage};
+use crate::containers::btc::BtcNodeContainer;
+use crate::containers::ggx::issue_pallet::vault_id;
+use crate::containers::ggx::vault_registry_pallet::VaultRegistryPallet;
+use crate::containers::ggx::GgxNodeContainer;
+use crate::images;
+use crate::metadata::ggx::runtime_types::interbtc_primitives::{CurrencyId, VaultId};
+
+/// a started vault must register all its collateral within this time
+pub const VAULT_READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Command line of a vault. Defaults to dev account `alice` registering with 500000000 GGXT,
+/// connected to bitcoind and GGX on their default ports of the host.
+#[derive(Debug, Clone)]
+pub struct VaultConfig {
+    keyring: String,
+    /// `(currency, amount)` for `--auto-register`, e.g. `("GGXT", 500000000)`
+    collateral: Vec<(String, u128)>,
+    auto_replace: bool,
+    prometheus_port: Option<u16>,
+    bitcoin_rpc_url: String,
+    bitcoin_rpc_user: String,
+    bitcoin_rpc_password: String,
+    electrs_url: Option<String>,
+    parachain_url: String,
+    extra: Vec<String>,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self::new("alice").with_collateral("GGXT", 500_000_000)
+    }
+}
+
+impl VaultConfig {
+    /// vault of dev account `keyring`, e.g. "bob", without collateral
+    pub fn new(keyring: &str) -> Self {
+        Self {
+            keyring: keyring.to_string(),
+            collateral: vec![],
+            auto_replace: true,
+            prometheus_port: None,
+            bitcoin_rpc_url: "http://127.0.0.1:18443".to_string(),
+            bitcoin_rpc_user: "bitcoin".to_string(),
+            bitcoin_rpc_password: "bitcoin".to_string(),
+            electrs_url: None,
+            parachain_url: "ws://127.0.0.1:9944".to_string(),
+            extra: vec![],
+        }
+    }
+
+    /// register a vault with `amount` of collateral `currency` (token symbol, e.g. "KSM") on
+    /// start, may be repeated for several collateral currencies
+    pub fn with_collateral(mut self, currency: &str, amount: u128) -> Self {
+        self.collateral.push((currency.to_string(), amount));
+        self
+    }
+
+    /// do not accept replace requests of other vaults automatically
+    pub fn without_auto_replace(mut self) -> Self {
+        self.auto_replace = false;
+        self
+    }
+
+    /// serve prometheus metrics on `port` of the host, disabled by default
+    pub fn with_prometheus_port(mut self, port: u16) -> Self {
+        self.prometheus_port = Some(port);
+        self
+    }
+
+    pub fn with_bitcoin_rpc(mut self, url: &str, user: &str, password: &str) -> Self {
+        self.bitcoin_rpc_url = url.to_string();
+        self.bitcoin_rpc_user = user.to_string();
+        self.bitcoin_rpc_password = password.to_string();
+        self
+    }
+
+    /// Esplora url of electrs, see [`ElectrsContainer::get_url`](crate::containers::electrs::ElectrsContainer::get_url)
+    pub fn with_electrs_url(mut self, url: &str) -> Self {
+        self.electrs_url = Some(url.to_string());
+        self
+    }
+
+    /// GGX websocket url
+    pub fn with_parachain_url(mut self, url: &str) -> Self {
+        self.parachain_url = url.to_string();
+        self
+    }
+
+    /// any other vault flag, e.g. `--no-issue-execution`
+    pub fn with_arg(mut self, arg: &str) -> Self {
+        self.extra.push(arg.to_string());
+        self
+    }
+
+    pub fn keyring(&self) -> &str {
+        &self.keyring
+    }
+
+    pub fn collateral(&self) -> &[(String, u128)] {
+        &self.collateral
+    }
+
+    pub fn prometheus_port(&self) -> Option<u16> {
+        self.prometheus_port
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        dev_keypair(&self.keyring).public_key().to_account_id()
+    }
+
+    /// vaults registered for each collateral
+    pub fn vault_ids(&self) -> Vec<VaultId<AccountId32, CurrencyId>> {
+        self.collateral
+            .iter()
+            .map(|(currency, _)| vault_id(self.account_id(), token_currency(currency)))
+            .collect()
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "vault",
+            "--restart-policy=never",
+            "--bitcoin-connection-timeout-ms=300",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        args.push(format!("--btc-parachain-url={}", self.parachain_url));
+        args.push(format!("--bitcoin-rpc-url={}", self.bitcoin_rpc_url));
+        args.push(format!("--bitcoin-rpc-user={}", self.bitcoin_rpc_user));
+        args.push(format!("--bitcoin-rpc-pass={}", self.bitcoin_rpc_password));
+        args.push(format!("--keyring={}", self.keyring));
+        if let Some(url) = &self.electrs_url {
+            args.push(format!("--electrs-url={url}"));
+        }
+        for (currency, amount) in &self.collateral {
+            args.push(format!("--auto-register={currency}={amount}"));
+        }
+        if !self.auto_replace {
+            args.push("--no-auto-replace".to_string());
+        }
+        match self.prometheus_port {
+            Some(port) => {
+                args.push(format!("--prometheus-port={port}"));
+                args.push("--prometheus-external".to_string());
+            }
+            None => args.push("--no-prometheus".to_string()),
+        }
+        args.extend(self.extra.iter().cloned());
+        args
+    }
+}
+
+pub struct VaultContainer {
+    inner: InterbtcClientsContainer,
+    cfg: VaultConfig,
+}
+
+impl VaultContainer {
+    pub fn from(container: InterbtcClientsContainer, cfg: VaultConfig) -> Self {
+        Self {
+            inner: container,
+            cfg,
+        }
+    }
+
+    pub fn container(&self) -> &InterbtcClientsContainer {
+        &self.inner
+    }
+
+    pub fn config(&self) -> &VaultConfig {
+        &self.cfg
+    }
+
+    pub fn account_id(&self) -> AccountId32 {
+        self.cfg.account_id()
+    }
+
+    pub fn vault_ids(&self) -> Vec<VaultId<AccountId32, CurrencyId>> {
+        self.cfg.vault_ids()
+    }
+
+    /// prometheus metrics url on the host, if enabled
+    pub fn get_metrics_url(&self) -> Option<String> {
+        self.cfg
+            .prometheus_port()
+            .map(|port| format!("http://127.0.0.1:{port}/metrics"))
+    }
+
+    /// Wait until the vault has registered all its collateral on `ggx`.
+    pub async fn wait_until_ready(&self, ggx: &GgxNodeContainer, timeout: Duration) {
+        for id in self.vault_ids() {
+            ggx.vault_registry_wait_for_vault(id, timeout).await;
+        }
+    }
+}
+
+/// Start a vault described by `cfg` on the host network, connected to `btc` and `ggx`, and wait
+/// until it is registered. The container is named `vault-<keyring>-<suffix>`, so vaults can run
+/// side by side, also in parallel tests.
+pub async fn start_vault(
+    btc: &BtcNodeContainer,
+    ggx: &GgxNodeContainer,
+    cfg: &VaultConfig,
+) -> VaultContainer {
+    log::info!("Starting Vault {}", cfg.keyring());
+
+    let (user, password) = btc.credentials().await;
+    let cfg = cfg
+        .clone()
+        .with_bitcoin_rpc(&btc.get_rpc_url().await, &user, &password)
+        .with_parachain_url(&ggx.get_host_ws_url().await);
+
+    images::require(&InterbtcClientsImage::brooklyn()).await;
+    let container = InterbtcClientsImage::vault(&cfg).start().await;
+    let vault = VaultContainer::from(InterbtcClientsContainer::from(container), cfg);
+    vault.wait_until_ready(ggx, VAULT_READY_TIMEOUT).await;
+    vault
+}
+
+/// Start a vault for each of `cfgs` concurrently, see [`start_vault`].
+pub async fn start_vaults(
+    btc: &BtcNodeContainer,
+    ggx: &GgxNodeContainer,
+    cfgs: &[VaultConfig],
+) -> Vec<VaultContainer> {
+    futures::future::join_all(cfgs.iter().map(|cfg| start_vault(btc, ggx, cfg))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_args() {
+        let args = VaultConfig::new("bob")
+            .with_collateral("KSM", 1000)
+            .with_collateral("GGXT", 2000)
+            .without_auto_replace()
+            .with_parachain_url("ws://127.0.0.1:9955")
+            .to_args();
+        assert_eq!(args[0], "vault");
+        assert!(args.contains(&"--keyring=bob".to_string()));
+        assert!(args.contains(&"--auto-register=KSM=1000".to_string()));
+        assert!(args.contains(&"--auto-register=GGXT=2000".to_string()));
+        assert!(args.contains(&"--no-auto-replace".to_string()));
+        assert!(args.contains(&"--no-prometheus".to_string()));
+        assert!(args.contains(&"--btc-parachain-url=ws://127.0.0.1:9955".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("--electrs-url")));
+
+        let args = VaultConfig::default().with_prometheus_port(9615).to_args();
+        assert!(args.contains(&"--keyring=alice".to_string()));
+        assert!(args.contains(&"--auto-register=GGXT=500000000".to_string()));
+        assert!(args.contains(&"--prometheus-port=9615".to_string()));
+        assert!(!args.contains(&"--no-prometheus".to_string()));
+        assert!(!args.contains(&"--no-auto-replace".to_string()));
+
+        let args = VaultConfig::default()
+            .with_electrs_url("http://127.0.0.1:3002")
+            .to_args();
+        assert!(args.contains(&"--electrs-url=http://127.0.0.1:3002".to_string()));
+    }
+
+    #[test]
+    fn test_vault_ids() {
+        let cfg = VaultConfig::new("bob")
+            .with_collateral("KSM", 1000)
+            .with_collateral("GGXT", 2000);
+        let ids = cfg.vault_ids();
+        assert_eq!(ids.len(), 2);
+        assert!(matches!(ids[0].currencies.collateral, CurrencyId::Token(_)));
+        assert_eq!(ids[1].account_id, cfg.account_id());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod btc;
 pub mod cosmos;
+pub mod electrs;
 pub mod ggx;
 pub mod hermes;
 pub mod interbtc_clients;
@@ -0,0 +1,139 @@
+//! Docker images used by testutil, and checks that they are available before tests start.
+//!
+//! A missing image otherwise shows up only as a testcontainers timeout. Run
+//! `cargo run --bin prefetch` once while online to pull everything listed in [`all`].
+
+use std::fmt::{Display, Formatter};
+use std::process::Stdio;
+
+use testcontainers::core::Image;
+use tokio::process::Command;
+
+use crate::containers::btc::BtcNodeImage;
+use crate::containers::cosmos::CosmosImage;
+use crate::containers::electrs::ElectrsImage;
+use crate::containers::ggx::GgxNodeImage;
+use crate::containers::hermes::HermesImage;
+use crate::containers::interbtc_clients::InterbtcClientsImage;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ImageRef {
+    pub name: String,
+    pub tag: String,
+}
+
+impl ImageRef {
+    pub fn new(name: &str, tag: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            tag: tag.to_string(),
+        }
+    }
+
+    pub fn of<I: Image>(image: &I) -> Self {
+        Self {
+            name: image.name(),
+            tag: image.tag(),
+        }
+    }
+}
+
+impl Display for ImageRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.name, self.tag)
+    }
+}
+
+/// every image:tag this crate starts by default
+pub fn all() -> Vec<ImageRef> {
+    vec![
+        ImageRef::of(&GgxNodeImage::brooklyn()),
+        ImageRef::of(&GgxNodeImage::sydney()),
+        ImageRef::of(&BtcNodeImage::default()),
+        ImageRef::of(&CosmosImage::default()),
+        ImageRef::of(&ElectrsImage::default()),
+        ImageRef::of(&HermesImage::default()),
+        ImageRef::of(&InterbtcClientsImage::brooklyn()),
+        ImageRef::of(&InterbtcClientsImage::sydney()),
+    ]
+}
+
+/// true if `image` is present in local docker
+pub async fn is_available_locally(image: &ImageRef) -> anyhow::Result<bool> {
+    let status = Command::new("docker")
+        .args(["image", "inspect", image.to_string().as_str()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| anyhow::anyhow!("cannot run docker, is it installed? {}", e))?;
+
+    Ok(status.success())
+}
+
+/// `docker pull image`
+pub async fn pull(image: &ImageRef) -> anyhow::Result<()> {
+    log::info!("Pulling image {}", image);
+    let output = Command::new("docker")
+        .args(["pull", image.to_string().as_str()])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("cannot run docker, is it installed? {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "cannot pull image {}: {}",
+            image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Pull every image from `images` which is not available locally.
+/// Fails with a message naming every image which is neither local nor can be pulled (e.g. offline).
+pub async fn ensure_available(images: &[ImageRef]) -> anyhow::Result<()> {
+    let mut missing = vec![];
+    for image in images {
+        if is_available_locally(image).await? {
+            log::debug!("Image {} is available locally", image);
+            continue;
+        }
+
+        if let Err(e) = pull(image).await {
+            missing.push(format!("  {image}: {e}"));
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "docker images are not available locally and cannot be pulled (are you offline? run `cargo run --bin prefetch` while online):\n{}",
+            missing.join("\n")
+        ))
+    }
+}
+
+/// panics with a readable message unless `image` is available, use before starting a container
+pub async fn require<I: Image>(image: &I) {
+    if let Err(e) = ensure_available(&[ImageRef::of(image)]).await {
+        panic!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_images_have_tags() {
+        let images = all();
+        assert!(images.contains(&ImageRef::new("ruimarinho/bitcoin-core", "22")));
+        assert!(images
+            .iter()
+            .all(|i| !i.name.is_empty() && !i.tag.is_empty() && i.tag != "latest"));
+    }
+}
@@ -4,21 +4,32 @@ pub use testcontainers::ContainerAsync;
 #[cfg(feature = "brooklyn")]
 pub mod metadata;
 
+pub mod balances;
 pub mod containers;
+pub mod images;
+pub mod logs;
+pub mod wait;
 
-/// in case of subxt error, panic with a meaningful message
-pub fn handle_tx_error(e: subxt::Error) -> ! {
+/// `(pallet, error)` names if `e` is a pallet error, e.g. `("Issue", "IssueCompleted")`
+pub fn module_error(e: &subxt::Error) -> Option<(String, String)> {
     match e {
         subxt::Error::Runtime(subxt::error::DispatchError::Module(error)) => {
             let details = error.details().expect("cannot get details");
-            let pallet = details.pallet.name();
-            let error = &details.variant;
-            panic!("Extrinsic failed with an error: {pallet}::{error:?}")
+            Some((
+                details.pallet.name().to_string(),
+                details.variant.name.to_string(),
+            ))
         }
-        _ => {
-            panic!("Extrinsic failed with an error: {}", e)
-        }
-    };
+        _ => None,
+    }
+}
+
+/// in case of subxt error, panic with a meaningful message
+pub fn handle_tx_error(e: subxt::Error) -> ! {
+    match module_error(&e) {
+        Some((pallet, error)) => panic!("Extrinsic failed with an error: {pallet}::{error}"),
+        None => panic!("Extrinsic failed with an error: {}", e),
+    }
 }
 
 /// macro vecs! which creates a Vec<String> from &str:
@@ -0,0 +1,519 @@
+//! Capture of container stdout/stderr.
+//!
+//! Every container wrapper in [`crate::containers`] streams its logs into an in-memory ring buffer
+//! as soon as it is created. When a test panics, logs of all containers started by that test are
+//! written to `target/testutil-logs/<test>/<container>.log`, and the directory is printed on the
+//! same line as the panic message. Logs are kept after the container stops, until its wrapper is
+//! dropped, so a crashed container is dumped too.
+//!
+//! Captured logs can also be inspected at runtime, see [`ContainerLogs::expect_log`],
+//! [`ContainerLogs::assert_no_log`] and [`ContainerLogs::log_stream`].
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, Once, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use regex::Regex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+
+/// how many last lines we keep for every container
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// how many lines a slow [`LogCapture::stream`] consumer may fall behind before it skips lines
+const STREAM_CAPACITY: usize = 1024;
+
+/// Logs of a single container, shared between the streaming task and the container wrapper.
+#[derive(Clone)]
+pub struct LogCapture {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    name: String,
+    test: String,
+    capacity: usize,
+    lines: Mutex<VecDeque<String>>,
+    /// dropped when the container stops, so that streams end
+    new_lines: Mutex<Option<broadcast::Sender<String>>>,
+}
+
+impl LogCapture {
+    fn new(name: String, test: String, capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                name,
+                test,
+                capacity,
+                lines: Mutex::new(VecDeque::with_capacity(capacity)),
+                new_lines: Mutex::new(Some(broadcast::channel(STREAM_CAPACITY).0)),
+            }),
+        }
+    }
+
+    /// name of the log file (without extension)
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// name of the test which started the container
+    pub fn test(&self) -> &str {
+        &self.inner.test
+    }
+
+    /// copy of the lines currently stored in the ring buffer, oldest first
+    pub fn lines(&self) -> Vec<String> {
+        self.inner.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn push(&self, line: String) {
+        let mut lines = self.inner.lines.lock().unwrap();
+        if lines.len() == self.inner.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line.clone());
+        if let Some(new_lines) = self.inner.new_lines.lock().unwrap().as_ref() {
+            // no receivers is fine
+            let _ = new_lines.send(line);
+        }
+    }
+
+    /// End all streams, called when the container stops printing.
+    fn close(&self) {
+        self.inner.new_lines.lock().unwrap().take();
+    }
+
+    /// receiver of new lines, already closed if the container stopped
+    fn subscribe(&self) -> broadcast::Receiver<String> {
+        match self.inner.new_lines.lock().unwrap().as_ref() {
+            Some(new_lines) => new_lines.subscribe(),
+            None => broadcast::channel(1).1,
+        }
+    }
+
+    /// buffered lines and a receiver of every line pushed after them, without gaps in between
+    fn lines_and_subscribe(&self) -> (Vec<String>, broadcast::Receiver<String>) {
+        let lines = self.inner.lines.lock().unwrap();
+        (lines.iter().cloned().collect(), self.subscribe())
+    }
+
+    /// stream of lines printed from now on
+    pub fn stream(&self) -> BoxStream<'static, String> {
+        receiver_stream(self.subscribe())
+    }
+
+    /// first buffered line matching `pattern`
+    pub fn find(&self, pattern: &str) -> Option<String> {
+        let re = compile(pattern);
+        self.lines().into_iter().find(|line| re.is_match(line))
+    }
+
+    /// Wait until a line matching `pattern` is printed and return it.
+    /// Lines printed before this call are matched too, so there is no race with the action
+    /// which is expected to produce the line.
+    pub async fn expect(&self, pattern: &str, timeout: Duration) -> String {
+        let re = compile(pattern);
+        let (lines, receiver) = self.lines_and_subscribe();
+        if let Some(line) = lines.into_iter().find(|line| re.is_match(line)) {
+            return line;
+        }
+
+        let mut stream = receiver_stream(receiver);
+        let found = tokio::time::timeout(timeout, async {
+            while let Some(line) = stream.next().await {
+                if re.is_match(&line) {
+                    return Some(line);
+                }
+            }
+            None
+        })
+        .await;
+
+        match found {
+            Ok(Some(line)) => line,
+            Ok(None) => panic!(
+                "container {} stopped without printing a line matching `{}`",
+                self.name(),
+                pattern
+            ),
+            Err(_) => panic!(
+                "timeout ({:?}) waiting for container {} to print a line matching `{}`",
+                timeout,
+                self.name(),
+                pattern
+            ),
+        }
+    }
+
+    /// panic if any buffered line matches `pattern`
+    pub fn assert_no_match(&self, pattern: &str) {
+        if let Some(line) = self.find(pattern) {
+            panic!(
+                "container {} printed a line matching `{}`: {}",
+                self.name(),
+                pattern,
+                line
+            );
+        }
+    }
+
+    /// write buffered lines to `target/testutil-logs/<test>/<container>.log` and return that path
+    pub fn dump(&self) -> std::io::Result<PathBuf> {
+        let dir = test_logs_dir(self.test());
+        std::fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!("{}.log", self.name()));
+        let mut content = self.lines().join("\n");
+        content.push('\n');
+        std::fs::write(&path, content)?;
+
+        Ok(path)
+    }
+}
+
+fn receiver_stream(receiver: broadcast::Receiver<String>) -> BoxStream<'static, String> {
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(line) => return Some((line, receiver)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Log stream is lagging, skipped {} lines", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .boxed()
+}
+
+fn compile(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap_or_else(|e| panic!("invalid log pattern `{pattern}`: {e}"))
+}
+
+/// Implemented by every container wrapper, gives access to its captured logs.
+#[async_trait]
+pub trait ContainerLogs {
+    /// docker id of the running container
+    fn container_id(&self) -> &str;
+
+    /// short name of the container, like `bitcoin` or `vault`
+    fn log_name(&self) -> &str;
+
+    /// logs of this container; capture is started on first call
+    fn logs(&self) -> LogCapture {
+        capture(self.container_id(), self.log_name())
+    }
+
+    /// write logs to `target/testutil-logs/<test>/<container>.log` and return that path
+    fn dump_logs(&self) -> PathBuf {
+        self.logs().dump().expect("cannot dump container logs")
+    }
+
+    /// Wait until the container prints a line matching regex `pattern` and return that line.
+    /// Lines printed before this call are matched as well.
+    async fn expect_log(&self, pattern: &str, timeout: Duration) -> String {
+        self.logs().expect(pattern, timeout).await
+    }
+
+    /// panic if the container has printed a line matching regex `pattern`
+    fn assert_no_log(&self, pattern: &str) {
+        self.logs().assert_no_match(pattern)
+    }
+
+    /// stream of lines the container prints from now on
+    fn log_stream(&self) -> BoxStream<'static, String> {
+        self.logs().stream()
+    }
+}
+
+fn registry() -> &'static Mutex<HashMap<String, LogCapture>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, LogCapture>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Start streaming logs of container `container_id` (if not started yet).
+/// `name` is used as the log file name, short container id is appended to keep it unique.
+pub fn capture(container_id: &str, name: &str) -> LogCapture {
+    install_panic_hook();
+
+    let mut registry = registry().lock().unwrap();
+    if let Some(capture) = registry.get(container_id) {
+        return capture.clone();
+    }
+
+    let short_id = &container_id[..container_id.len().min(12)];
+    let capture = LogCapture::new(
+        format!("{name}-{short_id}"),
+        current_test_name(),
+        DEFAULT_CAPACITY,
+    );
+
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(stream_logs(container_id.to_string(), capture.clone()));
+        }
+        Err(_) => log::warn!(
+            "Not in tokio runtime, logs of container {} will not be captured",
+            capture.name()
+        ),
+    }
+
+    registry.insert(container_id.to_string(), capture.clone());
+    capture
+}
+
+/// Dump logs of all containers started by `test`, returns the directory with log files.
+pub fn dump_test_logs(test: &str) -> Option<PathBuf> {
+    let captures: Vec<LogCapture> = registry()
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|c| c.test() == test)
+        .cloned()
+        .collect();
+
+    if captures.is_empty() {
+        return None;
+    }
+
+    for capture in captures {
+        if let Err(e) = capture.dump() {
+            log::error!("Cannot dump logs of {}: {}", capture.name(), e);
+        }
+    }
+
+    Some(test_logs_dir(test))
+}
+
+/// Forget logs of container `container_id`, called when its wrapper is dropped.
+pub fn forget(container_id: &str) {
+    registry().lock().unwrap().remove(container_id);
+}
+
+async fn stream_logs(container_id: String, capture: LogCapture) {
+    let child = Command::new("docker")
+        .args(["logs", "--follow", container_id.as_str()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            log::warn!("Cannot run `docker logs` for {}: {}", capture.name(), e);
+            return;
+        }
+    };
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    // `docker logs` ends by itself when the container is removed
+    futures::join!(
+        read_lines(stdout, capture.clone()),
+        read_lines(stderr, capture.clone())
+    );
+    let _ = child.wait().await;
+
+    // the entry stays registered, so that logs of a crashed container are still dumped on panic
+    capture.close();
+}
+
+async fn read_lines(stream: impl AsyncRead + Unpin, capture: LogCapture) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        capture.push(line);
+    }
+}
+
+/// Both `#[test]` and `#[tokio::test]` run on a thread named after the test.
+fn current_test_name() -> String {
+    match std::thread::current().name() {
+        Some("main") | None => "unknown".to_string(),
+        Some(name) => name.to_string(),
+    }
+}
+
+fn test_logs_dir(test: &str) -> PathBuf {
+    let target = std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("CARGO_MANIFEST_DIR").map(|d| PathBuf::from(d).join("target")))
+        .unwrap_or_else(|_| PathBuf::from("target"));
+
+    let test: String = test
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+
+    target.join("testutil-logs").join(test)
+}
+
+/// Chains the current panic hook: before it runs, container logs of the panicking test are
+/// dumped and the path is printed together with the panic message.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let test = current_test_name();
+            if let Some(dir) = dump_test_logs(&test) {
+                let location = info.location().map(|l| l.to_string());
+                eprintln!(
+                    "{}",
+                    panic_report(&test, location, panic_message(info.payload()), &dir)
+                );
+            }
+
+            previous(info);
+        }));
+    });
+}
+
+/// text of a `panic!` payload, which is either `&str` or `String`
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+/// single line with the panic message and the directory of dumped container logs
+fn panic_report(test: &str, location: Option<String>, message: &str, dir: &Path) -> String {
+    let location = location.map(|l| format!(" at {l}")).unwrap_or_default();
+    format!(
+        "test '{test}' panicked{location}: {}, container logs are saved to {}",
+        message.replace('\n', " "),
+        dir.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_keeps_last_lines() {
+        let capture = LogCapture::new("node".to_string(), "ring".to_string(), 3);
+        for i in 0..5 {
+            capture.push(format!("line {i}"));
+        }
+
+        assert_eq!(capture.lines(), vec!["line 2", "line 3", "line 4"]);
+    }
+
+    #[test]
+    fn test_dump_writes_file_per_container() {
+        let capture = LogCapture::new("node".to_string(), "logs::dump".to_string(), 3);
+        capture.push("hello".to_string());
+
+        let path = capture.dump().unwrap();
+        assert!(path.ends_with("testutil-logs/logs__dump/node.log"));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "hello\n");
+    }
+
+    #[tokio::test]
+    async fn test_expect_matches_past_and_new_lines() {
+        let capture = LogCapture::new("node".to_string(), "expect".to_string(), 10);
+        capture.push("vault::relay: Initializing at height 101".to_string());
+
+        let line = capture
+            .expect(r"Initializing at height \d+", Duration::from_secs(1))
+            .await;
+        assert_eq!(line, "vault::relay: Initializing at height 101");
+
+        let pusher = capture.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            pusher.push("Executing issue #1".to_string());
+        });
+        let line = capture
+            .expect("Executing issue", Duration::from_secs(5))
+            .await;
+        assert_eq!(line, "Executing issue #1");
+
+        capture.assert_no_match("(?i)error");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "timeout")]
+    async fn test_expect_times_out() {
+        let capture = LogCapture::new("node".to_string(), "timeout".to_string(), 10);
+        capture.expect("never", Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_new_lines() {
+        let capture = LogCapture::new("node".to_string(), "stream".to_string(), 10);
+        capture.push("old".to_string());
+
+        let mut stream = capture.stream();
+        capture.push("new".to_string());
+        assert_eq!(stream.next().await.unwrap(), "new");
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_when_closed() {
+        let capture = LogCapture::new("node".to_string(), "closed".to_string(), 10);
+        let mut stream = capture.stream();
+        capture.push("last".to_string());
+        capture.close();
+
+        assert_eq!(stream.next().await.unwrap(), "last");
+        assert!(stream.next().await.is_none());
+        assert!(capture.stream().next().await.is_none());
+        assert_eq!(capture.lines(), vec!["last"]);
+    }
+
+    #[test]
+    fn test_panic_report_is_single_line() {
+        let payload: Box<dyn Any + Send> = Box::new(format!("left != {}\nright", 1));
+        let report = panic_report(
+            "e2e_test",
+            Some("tests/e2e_test.rs:10:5".to_string()),
+            panic_message(payload.as_ref()),
+            Path::new("target/testutil-logs/e2e_test"),
+        );
+
+        assert_eq!(
+            report,
+            "test 'e2e_test' panicked at tests/e2e_test.rs:10:5: left != 1 right, \
+             container logs are saved to target/testutil-logs/e2e_test"
+        );
+    }
+
+    #[test]
+    fn test_closed_capture_is_dumped() {
+        let test = "logs::closed_dump";
+        let capture = LogCapture::new("crashed".to_string(), test.to_string(), 10);
+        capture.push("fatal error".to_string());
+        capture.close();
+        registry()
+            .lock()
+            .unwrap()
+            .insert("closed-dump-id".to_string(), capture);
+
+        let dir = dump_test_logs(test).expect("closed capture is not registered");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("crashed.log")).unwrap(),
+            "fatal error\n"
+        );
+
+        forget("closed-dump-id");
+        assert!(dump_test_logs(test).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Condition-based polling, use these instead of fixed `sleep`s in tests.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// default interval between two polls
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// interval between polls grows by this factor after every unsuccessful poll...
+const BACKOFF_FACTOR: f64 = 1.5;
+
+/// ...but not above this value (unless initial interval is even bigger)
+const MAX_BACKOFF_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Poll `probe` until it returns `Ok(T)`, and return `T`.
+///
+/// `Err(E)` means that the condition is not met yet, `E` is the last observed value - it is printed
+/// in the panic message if the condition is not met within `timeout`.
+/// Polling starts with `interval` between polls, which grows up to 5 seconds.
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+/// use testutil::wait::eventually;
+///
+/// let height = eventually(Duration::from_secs(60), Duration::from_millis(500), || async {
+///     let height = 42u32; // query something here
+///     if height >= 42 { Ok(height) } else { Err(height) }
+/// })
+/// .await;
+/// # }
+/// ```
+pub async fn eventually<T, E, F, Fut>(timeout: Duration, interval: Duration, mut probe: F) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Debug,
+{
+    let deadline = Instant::now() + timeout;
+    let max_interval = MAX_BACKOFF_INTERVAL.max(interval);
+    let mut interval = interval;
+    let mut last_observed: Option<E> = None;
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        match tokio::time::timeout_at(deadline, probe()).await {
+            Ok(Ok(value)) => return value,
+            Ok(Err(observed)) => {
+                log::debug!("Condition is not met yet, observed: {:?}", observed);
+                last_observed = Some(observed);
+            }
+            // probe itself did not finish in time
+            Err(_) => break,
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+
+        tokio::time::sleep(interval.min(deadline - now)).await;
+        interval = interval.mul_f64(BACKOFF_FACTOR).min(max_interval);
+    }
+
+    match last_observed {
+        Some(observed) => panic!(
+            "condition is not met within {:?} ({} attempts), last observed value: {:?}",
+            timeout, attempts, observed
+        ),
+        None => panic!(
+            "condition is not met within {:?}, no value was observed",
+            timeout
+        ),
+    }
+}
+
+/// Poll `probe` until `predicate` accepts its value, and return that value.
+/// On timeout, panics with the last value returned by `probe`.
+pub async fn wait_until<T, F, Fut, P>(
+    timeout: Duration,
+    interval: Duration,
+    mut probe: F,
+    predicate: P,
+) -> T
+where
+    T: Debug,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = T>,
+    P: Fn(&T) -> bool,
+{
+    let predicate = &predicate;
+    eventually(timeout, interval, || {
+        let value = probe();
+        async move {
+            let value = value.await;
+            if predicate(&value) {
+                Ok(value)
+            } else {
+                Err(value)
+            }
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_eventually_returns_first_ok() {
+        let counter = AtomicU32::new(0);
+        let value = eventually(
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+            || async {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                if n >= 3 {
+                    Ok(n)
+                } else {
+                    Err(n)
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(value, 3);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "last observed value: 7")]
+    async fn test_eventually_reports_last_observed_value() {
+        eventually(
+            Duration::from_millis(200),
+            Duration::from_millis(10),
+            || async { Err::<(), _>(7) },
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_predicate() {
+        let counter = AtomicU32::new(0);
+        let value = wait_until(
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+            || async { counter.fetch_add(1, Ordering::SeqCst) },
+            |n| *n == 2,
+        )
+        .await;
+
+        assert_eq!(value, 2);
+    }
+}
@@ -0,0 +1,72 @@
+use futures::join;
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::btc::{
+    bitcoincore_rpc::bitcoin::Amount, default_mining_address, start_host_btc,
+};
+use testutil::containers::electrs::start_electrs;
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::issue_pallet::IssuePallet;
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, OraclePallet};
+use testutil::containers::ggx::start_ggx;
+use testutil::containers::ggx::vault_registry_pallet::FIXED_ONE;
+use testutil::containers::interbtc_clients::{start_vault, VaultConfig};
+use testutil::metadata::ggx::runtime_types::{
+    interbtc_primitives::TokenSymbol, sp_arithmetic::fixed_point::FixedU128,
+};
+use testutil::vecs;
+
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+#[cfg(test)]
+mod e2e_btc_electrs_test {
+    use crate::*;
+
+    /// the vault reads BTC through electrs and still executes issues
+    #[tokio::test]
+    async fn e2e_btc_electrs_test() {
+        let _ = env_logger::builder().try_init();
+
+        let (bitcoin, ggx) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+
+        let ggxt = exchange_rate_key(TokenSymbol::GGXT);
+        let electrs = start_electrs(&bitcoin).await;
+        let _oracle = ggx
+            .oracle_start_fake_oracle(
+                dev::alice(),
+                Duration::from_secs(6),
+                vec![(ggxt.clone(), FixedU128(FIXED_ONE))],
+            )
+            .await;
+        ggx.oracle_wait_for_aggregate(ggxt, FixedU128(FIXED_ONE), TIMEOUT)
+            .await;
+
+        let wallet = bitcoin
+            .create_funded_wallet("test", Amount::from_btc(10.0).unwrap())
+            .await;
+        electrs.wait_for_sync(&bitcoin, TIMEOUT).await;
+
+        let cfg = VaultConfig::default().with_electrs_url(&electrs.get_url().await);
+        let vault = start_vault(&bitcoin, &ggx, &cfg).await;
+        let vault_id = vault.vault_ids().remove(0);
+
+        ggx.btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+        let _miner = bitcoin
+            .start_auto_miner(Duration::from_secs(1), &default_mining_address())
+            .await;
+        let receipt = ggx
+            .issue_btc(&bitcoin, &wallet, dev::alice(), vault_id, 500_000)
+            .await;
+
+        // the payment the vault proved is indexed by electrs
+        let tx = electrs
+            .esplora()
+            .await
+            .tx(&receipt.btc_txid)
+            .await
+            .expect("electrs does not know the issue payment");
+        assert!(tx.status.confirmed);
+        assert!(tx.vout.iter().any(|out| out.value == 500_000));
+    }
+}
@@ -0,0 +1,85 @@
+use futures::join;
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::btc::{
+    bitcoincore_rpc::bitcoin::Amount, default_mining_address, start_host_btc,
+};
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::issue_pallet::IssuePallet;
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, linear_curve, OraclePallet};
+use testutil::containers::ggx::start_ggx;
+use testutil::containers::ggx::vault_registry_pallet::{VaultRegistryPallet, FIXED_ONE};
+use testutil::containers::interbtc_clients::{start_vault, VaultConfig};
+use testutil::metadata::ggx::runtime_types::{
+    interbtc_primitives::TokenSymbol, sp_arithmetic::fixed_point::FixedU128,
+};
+use testutil::vecs;
+
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+#[cfg(test)]
+mod e2e_btc_liquidation_test {
+    use crate::*;
+
+    /// the fake oracle raises the BTC price until the vault can be liquidated
+    #[tokio::test]
+    async fn e2e_btc_liquidation_test() {
+        let _ = env_logger::builder().try_init();
+
+        let (bitcoin, ggx) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+
+        let ggxt = exchange_rate_key(TokenSymbol::GGXT);
+        let oracle = ggx
+            .oracle_start_fake_oracle(
+                dev::alice(),
+                Duration::from_secs(6),
+                vec![(ggxt.clone(), FixedU128(FIXED_ONE))],
+            )
+            .await;
+        ggx.oracle_wait_for_aggregate(ggxt.clone(), FixedU128(FIXED_ONE), TIMEOUT)
+            .await;
+
+        let vault = start_vault(&bitcoin, &ggx, &VaultConfig::default()).await;
+        let vault_id = vault.vault_ids().remove(0);
+
+        let wallet = bitcoin
+            .create_funded_wallet("test", Amount::from_btc(10.0).unwrap())
+            .await;
+        ggx.btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+        let _miner = bitcoin
+            .start_auto_miner(Duration::from_secs(1), &default_mining_address())
+            .await;
+        ggx.issue_btc(&bitcoin, &wallet, dev::alice(), vault_id.clone(), 500_000)
+            .await;
+
+        let collateralization = ggx
+            .vault_registry_collateralization(vault_id.clone())
+            .await
+            .expect("vault has issued tokens");
+        log::info!("Collateralization before: {}", collateralization);
+        assert!(collateralization > 2.0);
+
+        // BTC gets so expensive that the collateral covers half of the issued tokens
+        let target = FixedU128((collateralization * 2.0) as u128 * FIXED_ONE);
+        oracle.follow(linear_curve(
+            ggxt.clone(),
+            FixedU128(FIXED_ONE),
+            target.clone(),
+            5,
+        ));
+        ggx.oracle_wait_for_aggregate(ggxt, target, TIMEOUT).await;
+        assert!(oracle.is_script_done());
+
+        let collateralization = ggx
+            .vault_registry_collateralization(vault_id.clone())
+            .await
+            .unwrap();
+        assert!(collateralization < 1.0);
+        assert!(!ggx.vault_registry_is_liquidated(vault_id.clone()).await);
+
+        ggx.vault_registry_report_undercollateralized_vault(dev::bob(), vault_id.clone())
+            .await;
+        assert!(ggx.vault_registry_is_liquidated(vault_id).await);
+    }
+}
@@ -0,0 +1,144 @@
+use futures::join;
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::btc::{
+    bitcoincore_rpc::bitcoin::Amount, default_mining_address, start_host_btc,
+};
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::issue_pallet::IssuePallet;
+use testutil::containers::ggx::nomination_pallet::NominationPallet;
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, OraclePallet};
+use testutil::containers::ggx::start_ggx;
+use testutil::containers::ggx::tokens_pallet::TokensPallet;
+use testutil::containers::ggx::vault_registry_pallet::{VaultRegistryPallet, FIXED_ONE};
+use testutil::containers::interbtc_clients::{start_vault, VaultConfig};
+use testutil::metadata::ggx::runtime_types::{
+    interbtc_primitives::{CurrencyId, TokenSymbol},
+    sp_arithmetic::fixed_point::FixedU128,
+};
+use testutil::vecs;
+
+const COLLATERAL: u128 = 500_000_000;
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+#[cfg(test)]
+mod e2e_btc_nomination_test {
+    use crate::*;
+
+    /// issue fees are shared between the nominators of a vault by their stake
+    #[tokio::test]
+    async fn e2e_btc_nomination_test() {
+        let _ = env_logger::builder().try_init();
+
+        let (bitcoin, ggx) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+
+        let ggxt = exchange_rate_key(TokenSymbol::GGXT);
+        let _oracle = ggx
+            .oracle_start_fake_oracle(
+                dev::alice(),
+                Duration::from_secs(6),
+                vec![(ggxt.clone(), FixedU128(FIXED_ONE))],
+            )
+            .await;
+        ggx.oracle_wait_for_aggregate(ggxt, FixedU128(FIXED_ONE), TIMEOUT)
+            .await;
+
+        let vault = start_vault(&bitcoin, &ggx, &VaultConfig::default()).await;
+        let vault_id = vault.vault_ids().remove(0);
+
+        // Charlie nominates twice as much as Bob
+        let nominated = ggx
+            .nomination_nominate_vault(
+                dev::alice(),
+                vault_id.clone(),
+                vec![
+                    (dev::bob(), COLLATERAL / 5),
+                    (dev::charlie(), COLLATERAL * 2 / 5),
+                ],
+            )
+            .await;
+        assert!(ggx.nomination_is_opted_in(vault_id.clone()).await);
+        assert_eq!(
+            ggx.nomination_stake(
+                vault_id.clone(),
+                dev::charlie().public_key().to_account_id()
+            )
+            .await,
+            COLLATERAL * 2 / 5
+        );
+        assert_eq!(
+            ggx.vault_registry_collateral(vault_id.clone()).await,
+            COLLATERAL + nominated.nominated_collateral()
+        );
+
+        let wallet = bitcoin
+            .create_funded_wallet("test", Amount::from_btc(10.0).unwrap())
+            .await;
+        ggx.btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+        let _miner = bitcoin
+            .start_auto_miner(Duration::from_secs(1), &default_mining_address())
+            .await;
+        let receipt = ggx
+            .issue_btc(&bitcoin, &wallet, dev::alice(), vault_id.clone(), 1_000_000)
+            .await;
+        assert!(receipt.fee > 0);
+
+        let kbtc = CurrencyId::Token(TokenSymbol::KBTC);
+        let rewards = ggx
+            .nomination_withdraw_all_rewards(&nominated, kbtc.clone())
+            .await;
+        log::info!("Nominator rewards: {:?}", rewards);
+        assert!(rewards[0] > 0);
+        // rounding of fixed point rewards
+        assert!(rewards[1].abs_diff(rewards[0] * 2) <= 2);
+        assert_eq!(
+            ggx.nomination_reward(
+                vault_id.clone(),
+                dev::bob().public_key().to_account_id(),
+                kbtc
+            )
+            .await,
+            0
+        );
+
+        // nominators withdraw their collateral, it is refunded to their GGXT balance
+        let ggxt_currency = CurrencyId::Token(TokenSymbol::GGXT);
+        for (nominator, amount) in [
+            (dev::bob(), COLLATERAL / 10),
+            (dev::bob(), COLLATERAL / 10),
+            (dev::charlie(), COLLATERAL * 2 / 5),
+        ] {
+            let account = nominator.public_key().to_account_id();
+            let stake = ggx
+                .nomination_stake(vault_id.clone(), account.clone())
+                .await;
+            let balance = ggx
+                .tokens_free_balance(account.clone(), ggxt_currency.clone())
+                .await;
+
+            ggx.nomination_withdraw_collateral(nominator, vault_id.clone(), amount)
+                .await;
+            assert_eq!(
+                ggx.nomination_stake(vault_id.clone(), account.clone())
+                    .await,
+                stake - amount
+            );
+            assert_eq!(
+                ggx.tokens_free_balance(account, ggxt_currency.clone())
+                    .await,
+                balance + amount
+            );
+        }
+        assert_eq!(
+            ggx.vault_registry_collateral(vault_id.clone()).await,
+            COLLATERAL
+        );
+
+        // without nominators the vault opts out and keeps only its own collateral
+        ggx.nomination_opt_out(dev::alice(), vault_id.currencies.clone())
+            .await;
+        assert!(!ggx.nomination_is_opted_in(vault_id.clone()).await);
+        assert_eq!(ggx.vault_registry_collateral(vault_id).await, COLLATERAL);
+    }
+}
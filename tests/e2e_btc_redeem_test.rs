@@ -0,0 +1,114 @@
+use futures::join;
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::btc::{
+    bitcoincore_rpc::bitcoin::Amount, default_mining_address, start_host_btc,
+};
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::fee_pallet::{fee_amount, FeePallet};
+use testutil::containers::ggx::issue_pallet::IssuePallet;
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, OraclePallet};
+use testutil::containers::ggx::redeem_pallet::RedeemPallet;
+use testutil::containers::ggx::tokens_pallet::TokensPallet;
+use testutil::containers::ggx::vault_registry_pallet::FIXED_ONE;
+use testutil::containers::ggx::{start_ggx, SubstrateApi};
+use testutil::containers::interbtc_clients::{start_vault, VaultConfig};
+use testutil::metadata::ggx::redeem::events::ExecuteRedeem;
+use testutil::metadata::ggx::runtime_types::{
+    interbtc_primitives::{CurrencyId, TokenSymbol},
+    sp_arithmetic::fixed_point::FixedU128,
+};
+use testutil::vecs;
+
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+const ISSUED: u128 = 500_000;
+const REDEEMED: u128 = 200_000;
+
+#[cfg(test)]
+mod e2e_btc_redeem_test {
+    use crate::*;
+
+    /// the vault pays a redeem request in BTC and executes it
+    #[tokio::test]
+    async fn e2e_btc_redeem_test() {
+        let _ = env_logger::builder().try_init();
+
+        let (bitcoin, ggx) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+
+        let ggxt = exchange_rate_key(TokenSymbol::GGXT);
+        let _oracle = ggx
+            .oracle_start_fake_oracle(
+                dev::alice(),
+                Duration::from_secs(6),
+                vec![(ggxt.clone(), FixedU128(FIXED_ONE))],
+            )
+            .await;
+        ggx.oracle_wait_for_aggregate(ggxt, FixedU128(FIXED_ONE), TIMEOUT)
+            .await;
+
+        let vault = start_vault(&bitcoin, &ggx, &VaultConfig::default()).await;
+        let vault_id = vault.vault_ids().remove(0);
+
+        let wallet = bitcoin
+            .create_funded_wallet("test", Amount::from_btc(10.0).unwrap())
+            .await;
+        ggx.btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+        let _miner = bitcoin
+            .start_auto_miner(Duration::from_secs(1), &default_mining_address())
+            .await;
+        let receipt = ggx
+            .issue_btc(&bitcoin, &wallet, dev::alice(), vault_id.clone(), ISSUED)
+            .await;
+
+        let alice = dev::alice().public_key().to_account_id();
+        let kbtc = CurrencyId::Token(TokenSymbol::KBTC);
+        assert_eq!(
+            ggx.tokens_free_balance(alice.clone(), kbtc.clone()).await,
+            receipt.amount
+        );
+
+        // redeem to a fresh wallet, so that its balance is exactly the vault's payment
+        let redeem_wallet = bitcoin.create_wallet("redeem").await;
+        let btc_address = redeem_wallet.new_address().await;
+        let request = ggx
+            .redeem_request_redeem(dev::alice(), REDEEMED, &btc_address, vault_id.clone())
+            .await;
+        log::info!("Redeem requested: {:?}", request);
+        assert_eq!(request.premium, 0);
+        assert_eq!(
+            request.amount + request.fee + request.transfer_fee,
+            REDEEMED
+        );
+        let expected_fee = fee_amount(REDEEMED, &ggx.fee_redeem_fee().await);
+        assert!(expected_fee > 0);
+        assert!(request.fee.abs_diff(expected_fee) <= 1);
+        assert_eq!(
+            ggx.tokens_free_balance(alice, kbtc).await,
+            receipt.amount - REDEEMED
+        );
+
+        // the vault pays the amount minus the redeem fee, committing to the redeem id
+        let payment = ggx
+            .redeem_wait_for_payment(&bitcoin, request.redeem_id, TIMEOUT)
+            .await;
+        assert_eq!(payment.amount, Amount::from_sat(request.amount as u64));
+        assert!(payment.tx.output.iter().any(|o| {
+            o.script_pubkey.is_op_return()
+                && o.script_pubkey.as_bytes().ends_with(&request.redeem_id.0)
+        }));
+
+        let (_, executed) = join!(
+            ggx.redeem_wait_for_completed(request.redeem_id, TIMEOUT),
+            ggx.wait_for_event::<ExecuteRedeem>(TIMEOUT)
+        );
+        log::info!("ExecuteRedeem found: {:?}", executed);
+        assert_eq!(executed.redeem_id, request.redeem_id);
+        assert_eq!(executed.vault_id.account_id, vault_id.account_id);
+        assert_eq!(executed.amount, request.amount);
+
+        // the payment is confirmed once the redeem is executed
+        assert_eq!(redeem_wallet.balance().await, payment.amount);
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use subxt_signer::sr25519::dev;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::RunnableImage;
+use testutil::containers::btc::{BtcNodeContainer, BtcNodeImage};
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::start_ggx;
+use testutil::{images, vecs};
+
+#[cfg(test)]
+mod e2e_btc_relay_test {
+    use crate::*;
+
+    /// the test itself acts as a relayer, no vault is started
+    #[tokio::test]
+    async fn e2e_btc_relay_test() {
+        let _ = env_logger::builder().try_init();
+
+        let image = BtcNodeImage::default();
+        images::require(&image).await;
+        let bitcoin = BtcNodeContainer::from(RunnableImage::from(image).start().await);
+        let ggx = start_ggx(vecs!["--alice"]).await;
+
+        bitcoin.mine(10).await;
+        ggx.btc_relay_relay_blocks(dev::alice(), &bitcoin).await;
+        ggx.btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+        assert_eq!(ggx.btc_relay_best_block_height().await, 10);
+
+        bitcoin.mine(5).await;
+        ggx.btc_relay_relay_blocks(dev::alice(), &bitcoin).await;
+        let best = ggx
+            .btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+        let header = ggx
+            .btc_relay_block_header(&best)
+            .await
+            .expect("best block is not stored");
+        assert_eq!(header.block_height, 15);
+
+        // replace the last 2 blocks, the relay stores new blocks as a fork first
+        bitcoin.reorg(2, 3).await;
+        ggx.btc_relay_relay_blocks(dev::alice(), &bitcoin).await;
+        assert!(ggx.btc_relay_chains().await.len() >= 2);
+
+        // the relay switches to the fork once it is long enough
+        bitcoin
+            .mine_until(Duration::from_secs(1), Duration::from_secs(300), || async {
+                ggx.btc_relay_relay_blocks(dev::alice(), &bitcoin).await;
+                ggx.btc_relay_best_block().await == Some(bitcoin.best_block_hash().await)
+            })
+            .await;
+    }
+}
@@ -0,0 +1,113 @@
+use futures::join;
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::btc::{
+    bitcoincore_rpc::bitcoin::Amount, default_mining_address, start_host_btc,
+};
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::issue_pallet::IssuePallet;
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, OraclePallet};
+use testutil::containers::ggx::replace_pallet::ReplacePallet;
+use testutil::containers::ggx::vault_registry_pallet::{VaultRegistryPallet, FIXED_ONE};
+use testutil::containers::ggx::{start_ggx, GgxNodeContainer};
+use testutil::containers::interbtc_clients::{start_vaults, VaultConfig};
+use testutil::metadata::ggx::runtime_types::{
+    interbtc_primitives::TokenSymbol, sp_arithmetic::fixed_point::FixedU128,
+};
+use testutil::vecs;
+
+const AMOUNT: u128 = 500_000;
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+async fn set_oracle_exchange_rate(ggx: &GgxNodeContainer) {
+    let key = exchange_rate_key(TokenSymbol::GGXT);
+    ggx.oracle_feed_values(dev::alice(), vec![(key.clone(), FixedU128(FIXED_ONE))])
+        .await;
+    ggx.oracle_wait_for_aggregate(key, FixedU128(FIXED_ONE), TIMEOUT)
+        .await;
+}
+
+#[cfg(test)]
+mod e2e_btc_replace_test {
+    use crate::*;
+
+    /// Alice's vault holds issued KBTC and is replaced by Bob's vault.
+    #[tokio::test]
+    async fn e2e_btc_replace_test() {
+        let _ = env_logger::builder().try_init();
+
+        let (bitcoin, ggx) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+        set_oracle_exchange_rate(&ggx).await;
+
+        // Bob accepts the replace below, his client must not do it on its own
+        let new_vault = VaultConfig::new("bob")
+            .with_collateral("GGXT", 500_000_000)
+            .without_auto_replace();
+        // both vaults register themselves
+        let vaults = start_vaults(&bitcoin, &ggx, &[VaultConfig::default(), new_vault]).await;
+        let old_vault_id = vaults[0].vault_ids().remove(0);
+        let new_vault_id = vaults[1].vault_ids().remove(0);
+
+        let wallet = bitcoin
+            .create_funded_wallet("test", Amount::from_btc(10.0).unwrap())
+            .await;
+        ggx.btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
+
+        // mine blocks in background, so that the vaults keep relaying them
+        let _miner = bitcoin
+            .start_auto_miner(Duration::from_secs(1), &default_mining_address())
+            .await;
+        let issued = ggx
+            .issue_btc(
+                &bitcoin,
+                &wallet,
+                dev::alice(),
+                old_vault_id.clone(),
+                AMOUNT,
+            )
+            .await;
+
+        let old_vault = ggx
+            .vault_registry_get_vault(old_vault_id.clone())
+            .await
+            .expect("old vault is registered");
+        assert_eq!(old_vault.issued_tokens, issued.amount + issued.fee);
+        assert_eq!(old_vault.to_be_issued_tokens, 0);
+
+        let btc_address = bitcoin.create_wallet("bob").await.new_address().await;
+        let receipt = ggx
+            .replace_vault(
+                dev::alice(),
+                old_vault_id.clone(),
+                dev::bob(),
+                new_vault_id.clone(),
+                issued.amount + issued.fee,
+                100_000_000,
+                &btc_address,
+                TIMEOUT,
+            )
+            .await;
+        log::info!("Replace completed: {:?}", receipt);
+        assert_eq!(receipt.amount, issued.amount + issued.fee);
+
+        let requests = ggx.replace_get_vault_replace_requests(&new_vault_id).await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].0, receipt.replace_id);
+
+        // all issued tokens moved from Alice's vault to Bob's
+        let old_vault = ggx
+            .vault_registry_get_vault(old_vault_id)
+            .await
+            .expect("old vault is registered");
+        assert_eq!(old_vault.issued_tokens, 0);
+        assert_eq!(old_vault.to_be_issued_tokens, 0);
+        assert_eq!(old_vault.to_be_replaced_tokens, 0);
+        let new_vault = ggx
+            .vault_registry_get_vault(new_vault_id)
+            .await
+            .expect("new vault is registered");
+        assert_eq!(new_vault.issued_tokens, receipt.amount);
+        assert_eq!(new_vault.to_be_issued_tokens, 0);
+    }
+}
@@ -1,279 +1,48 @@
 use futures::join;
-use hex::ToHex;
 use std::time::Duration;
-use subxt::{OnlineClient, PolkadotConfig};
 use subxt_signer::sr25519::dev;
-use testcontainers::core::WaitFor;
-use testcontainers::runners::AsyncRunner;
-use testcontainers::RunnableImage;
-use testutil::containers::ggx::SubstrateApi;
+use testutil::containers::ggx::btc_relay_pallet::BtcRelayPallet;
+use testutil::containers::ggx::fee_pallet::{fee_amount, FeePallet};
+use testutil::containers::ggx::issue_pallet::{vault_id, IssuePallet, IssueReceipt, ISSUE_TIMEOUT};
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, OraclePallet};
+use testutil::containers::ggx::tokens_pallet::TokensPallet;
+use testutil::containers::ggx::vault_registry_pallet::FIXED_ONE;
 use testutil::containers::{
     btc::{
-        bitcoincore_rpc::{
-            bitcoin::{Address, Amount, Network, Script, Txid},
-            Client as RpcClient, RpcApi,
-        },
-        BtcNodeContainer, BtcNodeImage,
+        bitcoincore_rpc::bitcoin::Amount, default_mining_address, start_host_btc, BtcNodeContainer,
+        BtcWallet,
     },
-    ggx::start_ggx,
-    interbtc_clients::{InterbtcClientsContainer, InterbtcClientsImage},
+    ggx::{start_ggx, GgxNodeContainer, SubstrateApi},
+    interbtc_clients::{start_vault, VaultConfig},
 };
+use testutil::metadata::ggx::issue::events::ExecuteIssue;
 use testutil::metadata::ggx::runtime_types::{
-    interbtc_primitives::{oracle::Key, CurrencyId, TokenSymbol},
+    interbtc_primitives::{CurrencyId, TokenSymbol},
     sp_arithmetic::fixed_point::FixedU128,
 };
 use testutil::vecs;
-use tokio::time::timeout;
-
-async fn start_btc() -> BtcNodeContainer {
-    log::info!("Starting Bitcoin");
-    let image = BtcNodeImage::default();
-    let image = RunnableImage::from(image)
-        .with_network("host")
-        .with_container_name("bitcoin");
-    BtcNodeContainer(image.start().await)
-}
-
-async fn start_vault(btc: &BtcNodeContainer, ggx_ws: String) -> InterbtcClientsContainer {
-    log::info!("Starting Vault");
-
-    let args = [
-        "vault",
-        "--no-prometheus",
-        "--restart-policy=never",
-        format!("--btc-parachain-url={}", ggx_ws).as_str(),
-        "--auto-register=GGXT=500000000",
-        "--bitcoin-connection-timeout-ms=300",
-        "--bitcoin-rpc-url=http://127.0.0.1:18443",
-        "--bitcoin-rpc-user",
-        btc.get_username().as_str(),
-        "--bitcoin-rpc-pass",
-        btc.get_password().as_str(),
-        "--keyring=alice",
-    ]
-    .iter()
-    .map(|s| s.to_string())
-    .collect();
-
-    let mut image = InterbtcClientsImage::brooklyn();
-    image.wait_for.push(WaitFor::message_on_stderr(
-        "vault::relay: Initializing at height",
-    ));
-    let image = (image, args);
-    let image = RunnableImage::from(image)
-        .with_network("host")
-        .with_container_name("vault");
-    InterbtcClientsContainer(image.start().await)
-}
-
-async fn set_oracle_exchange_rate(api: &OnlineClient<PolkadotConfig>) {
-    // use subxt to connect to the parachain and set the exchange rate for GGXT.
-    // normally `oracle` component does that, but in our setup it is not available.
-    let tx = testutil::metadata::ggx::tx().oracle().feed_values(vec![(
-        Key::ExchangeRate(CurrencyId::Token(TokenSymbol::GGXT)),
-        FixedU128(1_000_000_000_000_000_000u128),
-    )]);
-
-    let pair = dev::alice();
-
-    let wait = api
-        .tx()
-        .sign_and_submit_then_watch_default(&tx, &pair)
-        .await
-        .expect("cannot submit tx");
-
-    wait.wait_for_finalized_success().await.unwrap();
-}
-
-async fn get_best_btc_block_hash(api: &OnlineClient<PolkadotConfig>) -> Option<String> {
-    let query = testutil::metadata::ggx::storage().btc_relay().best_block();
-    let result = api
-        .storage()
-        .at_latest()
-        .await
-        .expect("cannot get storage at latest")
-        .fetch(&query)
-        .await
-        .expect("cannot get btc best block");
-
-    result.map(|b| hex::encode_upper(b.content))
-}
-
-async fn wait_for_btc_tree_sync(
-    bitcoin_api: &RpcClient,
-    api: &OnlineClient<PolkadotConfig>,
-    timeout_duration: Duration,
-) {
-    timeout(timeout_duration, async move {
-        loop {
-            // get last BTC block hash in Bitcoin
-            let btc_best: String = bitcoin_api
-                .get_best_block_hash()
-                .unwrap()
-                .encode_hex_upper();
-
-            let best_btc_ggx = get_best_btc_block_hash(api).await;
-            if let Some(btc_best_ggx) = best_btc_ggx {
-                log::debug!(
-					"Waiting for the parachain to ingest the last BTC block... Current: {}. BTC Best: {}",
-					btc_best_ggx, btc_best
-				);
-
-                if btc_best_ggx == btc_best {
-                    break;
-                }
-            }
-
-            tokio::time::sleep(Duration::from_secs(1)).await;
-        }
-
-        log::info!("Parachain and Bitcoin bitcoin best blocks are in sync")
-    })
-    .await
-    .expect("timeout waiting for btc tree sync");
-}
-
-fn create_btc_address_with_50btc(bitcoin: &BtcNodeContainer) -> Address {
-    log::info!("Creating BTC addr with 50 BTC");
-    // without this we cannot create new address
-    let bitcoin_api = bitcoin.api_with_host_network(None);
-    bitcoin_api
-        .create_wallet("test", None, None, None, None)
-        .expect("failed to create wallet");
-
-    // specify the wallet for next wallet operations
-    let bitcoin_api = bitcoin.api_with_host_network(Some("wallet/test"));
-
-    let address = bitcoin_api
-        .get_new_address(Some("test"), None)
-        .expect("Failed to get new address")
-        .require_network(Network::Regtest)
-        .expect("Should use regtest network");
-
-    // mine ourselves 50 BTC (coinbase is unlocked after 100 confirmations)
-    bitcoin_api.generate_to_address(101, &address).unwrap();
-    let balance = bitcoin_api.get_balance(None, None).unwrap();
-    assert_eq!(balance.to_btc(), 50.0);
-
-    address
-}
-
-async fn wait_until_btc_tx_finalized(
-    bitcoin_api: &RpcClient,
-    txid: &Txid,
-    confirmations: i32,
-    timeout_duration: Duration,
-) {
-    timeout(timeout_duration, async move {
-        loop {
-            let tx = bitcoin_api.get_transaction(txid, None).unwrap();
-            if tx.info.confirmations >= confirmations {
-                log::info!(
-                    "BTC tx {} is finalized with {} confirmations (block {}:{:?})",
-                    txid,
-                    tx.info.confirmations,
-                    tx.info.blockheight.unwrap(),
-                    tx.info.blockhash.unwrap()
-                );
-                break;
-            }
-
-            tokio::time::sleep(Duration::from_secs(1)).await;
-        }
-    })
-    .await
-    .expect("timeout waiting for btc tx to be finalized")
-}
 
 const AMOUNT: u64 = 500_000u64;
 
 async fn deposit_btc_to_ggx(
-    bitcoin_api: &RpcClient,
-    api: &OnlineClient<PolkadotConfig>,
-    bitcoin_user_address: &Address,
-) {
+    bitcoin: &BtcNodeContainer,
+    wallet: &BtcWallet,
+    ggx: &GgxNodeContainer,
+) -> IssueReceipt {
     log::info!("Depositing some BTC to GGX");
 
-    use testutil::metadata::ggx::runtime_types::interbtc_primitives::{VaultCurrencyPair, VaultId};
-
-    let ggxt = CurrencyId::Token(TokenSymbol::GGXT);
-    let kbtc = CurrencyId::Token(TokenSymbol::KBTC);
-
     let alice = dev::alice();
-    let vault_id = VaultId {
-        account_id: alice.public_key().to_account_id(),
-        currencies: VaultCurrencyPair {
-            collateral: ggxt,
-            wrapped: kbtc,
-        },
-    };
-
-    let tx = testutil::metadata::ggx::tx().issue().request_issue(
-        AMOUNT.into(),
-        vault_id,
+    let vault_id = vault_id(
+        alice.public_key().to_account_id(),
         CurrencyId::Token(TokenSymbol::GGXT),
     );
 
-    let wait = api
-        .tx()
-        .sign_and_submit_then_watch_default(&tx, &alice)
-        .await
-        .expect("cannot submit tx");
-
-    let events = match wait.wait_for_finalized_success().await {
-        Ok(result) => result,
-        Err(err) => testutil::handle_tx_error(err),
-    };
-
-    let e = events
-        .find_first::<testutil::metadata::ggx::issue::events::RequestIssue>()
-        .expect("no RequestIssue event")
-        .expect("Option is None");
-
-    let amount = Amount::from_sat(AMOUNT);
-    let script_pub_key = e.vault_address.0.to_script_pub_key();
-    let script = Script::from_bytes(script_pub_key.as_bytes());
-    let addr = Address::from_script(script, Network::Regtest).expect("bad address");
-    let txid = bitcoin_api
-        .send_to_address(
-            &addr,
-            amount,
-            Some("deposit"),
-            None,
-            None,
-            None,
-            Some(6),
-            None,
-        )
-        .expect("failed to send to address");
-
-    // wait a bit
-    tokio::time::sleep(Duration::from_secs(2)).await;
-
-    // mine 10 new blocks to include txid into a block + mine some blocks on top of it
-    bitcoin_api
-        .generate_to_address(10, bitcoin_user_address)
-        .unwrap();
-
-    // check if tx is included in a block
-    wait_until_btc_tx_finalized(bitcoin_api, &txid, 6, Duration::from_secs(60)).await;
-}
-
-async fn get_token_balance(
-    api: &OnlineClient<PolkadotConfig>,
-    account_id: subxt::utils::AccountId32,
-    token: CurrencyId,
-) -> Option<testutil::metadata::ggx::runtime_types::orml_tokens::AccountData<u128>> {
-    let query = testutil::metadata::ggx::storage()
-        .tokens()
-        .accounts(account_id, token);
-    api.storage()
-        .at_latest()
-        .await
-        .expect("cannot get storage at latest")
-        .fetch(&query)
+    // mine blocks in background, so that the vault keeps relaying them
+    let _miner = bitcoin
+        .start_auto_miner(Duration::from_secs(1), &default_mining_address())
+        .await;
+    ggx.issue_btc(bitcoin, wallet, alice, vault_id, AMOUNT.into())
         .await
-        .expect("cannot get token balance")
 }
 
 #[cfg(test)]
@@ -285,67 +54,73 @@ mod e2e_btc_test {
         let _ = env_logger::builder().try_init();
 
         // run in this order: Bitcoin, Parachain, Vault.
-        let (bitcoin, alice) = join!(start_btc(), start_ggx(vecs!["--alice"]));
-
-        // use subxt to connect to the parachain and set the exchange rate
-        let api = OnlineClient::<PolkadotConfig>::from_url(alice.get_host_ws_url().await)
-            .await
-            .expect("failed to connect to the parachain");
+        let (bitcoin, alice) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+        let ggxt = exchange_rate_key(TokenSymbol::GGXT);
+
+        // normally `oracle` component feeds the exchange rate, but in our setup it is not available
+        let rate = FixedU128(FIXED_ONE);
+        let _oracle = alice
+            .oracle_start_fake_oracle(
+                dev::alice(),
+                Duration::from_secs(30),
+                vec![(ggxt.clone(), rate.clone())],
+            )
+            .await;
+        alice
+            .oracle_wait_for_aggregate(ggxt, rate, Duration::from_secs(60))
+            .await;
 
-        set_oracle_exchange_rate(&api).await;
+        // 0.5%, `AMOUNT` is a multiple of 200, so the fee does not depend on rounding
+        let issue_fee = FixedU128(FIXED_ONE / 200);
+        alice.fee_set_issue_fee(issue_fee.clone()).await;
 
-        // let _faucet = start_faucet(&docker);
-        let _vault = start_vault(&bitcoin, alice.get_host_ws_url().await).await;
+        let _vault = start_vault(&bitcoin, &alice, &VaultConfig::default()).await;
 
-        let bitcoin_api = bitcoin.api_with_host_network(None);
-        let address = create_btc_address_with_50btc(&bitcoin);
+        // mine ourselves 50 BTC
+        let wallet = bitcoin
+            .create_funded_wallet("test", Amount::from_btc(50.0).unwrap())
+            .await;
+        assert_eq!(wallet.balance().await.to_btc(), 50.0);
 
         // wait for the parachain to ingest the last BTC block (at most 60 sec).
         // at this point vault should initialize GGX BTC tree with last block (101).
-        wait_for_btc_tree_sync(&bitcoin_api, &api, Duration::from_secs(60)).await;
+        alice
+            .btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(60))
+            .await;
 
         // mine another 20 blocks. Vault should send them to GGX automatically, 16 blocks at most at a time.
         // vault will send 2 batches...
-        bitcoin_api.generate_to_address(20, &address).unwrap();
+        bitcoin.mine(20).await;
 
         // wait for sync again, to confirm that vault
-        wait_for_btc_tree_sync(&bitcoin_api, &api, Duration::from_secs(120)).await;
-
-        // transfer BTC to GGX (TBTC)
-        deposit_btc_to_ggx(
-            &bitcoin.api_with_host_network(Some("wallet/test")),
-            &api,
-            &address,
-        )
-        .await;
+        alice
+            .btc_relay_wait_for_sync_with(&bitcoin, Duration::from_secs(120))
+            .await;
 
-        // and wait again...
-        wait_for_btc_tree_sync(&bitcoin_api, &api, Duration::from_secs(60)).await;
+        // transfer BTC to GGX (KBTC), the vault must execute the issue
+        let (receipt, executed) = join!(
+            deposit_btc_to_ggx(&bitcoin, &wallet, &alice),
+            alice.wait_for_event::<ExecuteIssue>(ISSUE_TIMEOUT)
+        );
+        log::info!("Issue completed: {:?}", receipt);
+        log::info!("ExecuteIssue found: {:?}", executed);
+        assert_eq!(executed.issue_id, receipt.issue_id);
+        assert_eq!(executed.vault_id.account_id, receipt.vault_id.account_id);
+        assert_eq!(executed.amount, receipt.amount);
+        assert_eq!(receipt.btc_paid, Amount::from_sat(AMOUNT));
+        assert_eq!(receipt.amount + receipt.fee, AMOUNT as u128);
 
-        // wait for ExecuteIssue event
-        let e = alice
-            .wait_for_event::<testutil::metadata::ggx::issue::events::ExecuteIssue>(
-                Duration::from_secs(60),
+        // check if Alice has KBTC that we deposited
+        let balance = alice
+            .tokens_free_balance(
+                dev::alice().public_key().to_account_id(),
+                CurrencyId::Token(TokenSymbol::KBTC),
             )
             .await;
-        log::warn!("ExecuteIssue found: {:?}", e);
-
-        // check if Alice has KBTC that we deposited
-        let alice_pair = dev::alice();
-        let balance = get_token_balance(
-            &api,
-            alice_pair.public_key().to_account_id(),
-            CurrencyId::Token(TokenSymbol::KBTC),
-        )
-        .await;
-
-        match balance {
-            Some(b) => {
-                assert!(b.free > 0);
-                // we should deduct fees
-                assert!(b.free < AMOUNT.into());
-            }
-            None => panic!("Alice's KBTC balance is None"),
-        };
+        assert!(balance > 0);
+        // we should deduct fees, exactly the issue fee we have set
+        assert_eq!(receipt.fee, fee_amount(AMOUNT.into(), &issue_fee));
+        assert_eq!(receipt.fee, 2_500);
+        assert_eq!(balance, receipt.amount);
     }
 }
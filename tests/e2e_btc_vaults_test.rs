@@ -0,0 +1,124 @@
+use futures::join;
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::btc::start_host_btc;
+use testutil::containers::ggx::issue_pallet::vault_id;
+use testutil::containers::ggx::oracle_pallet::{exchange_rate_key, OraclePallet};
+use testutil::containers::ggx::reward_pallet::RewardPallet;
+use testutil::containers::ggx::tokens_pallet::TokensPallet;
+use testutil::containers::ggx::vault_registry_pallet::{VaultRegistryPallet, FIXED_ONE};
+use testutil::containers::ggx::{start_ggx, GgxNodeContainer};
+use testutil::containers::interbtc_clients::{start_vaults, VaultConfig};
+use testutil::metadata::ggx::runtime_types::{
+    interbtc_primitives::{CurrencyId, TokenSymbol},
+    sp_arithmetic::fixed_point::FixedU128,
+};
+use testutil::vecs;
+
+const COLLATERAL: u128 = 500_000_000;
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// KSM is not used as collateral by default: configure it and give Bob some.
+async fn setup_ksm_collateral(ggx: &GgxNodeContainer) {
+    let ksm = vault_id(
+        dev::bob().public_key().to_account_id(),
+        CurrencyId::Token(TokenSymbol::KSM),
+    )
+    .currencies;
+    ggx.vault_registry_set_collateral_limits(ksm.clone(), COLLATERAL / 10, u128::MAX / 2)
+        .await;
+    ggx.vault_registry_set_thresholds(
+        ksm,
+        FixedU128(FIXED_ONE * 3 / 2),
+        FixedU128(FIXED_ONE * 13 / 10),
+        FixedU128(FIXED_ONE * 11 / 10),
+    )
+    .await;
+
+    ggx.tokens_set_balance(
+        dev::bob().public_key().to_account_id(),
+        CurrencyId::Token(TokenSymbol::KSM),
+        COLLATERAL * 10,
+        0,
+    )
+    .await;
+}
+
+async fn set_oracle_exchange_rates(ggx: &GgxNodeContainer) {
+    let ksm = exchange_rate_key(TokenSymbol::KSM);
+    ggx.oracle_feed_values(
+        dev::alice(),
+        vec![
+            (exchange_rate_key(TokenSymbol::GGXT), FixedU128(FIXED_ONE)),
+            // rates are collateral planck per satoshi
+            (ksm.clone(), FixedU128(FIXED_ONE * 2)),
+        ],
+    )
+    .await;
+    ggx.oracle_wait_for_aggregate(ksm, FixedU128(FIXED_ONE * 2), TIMEOUT)
+        .await;
+}
+
+#[cfg(test)]
+mod e2e_btc_vaults_test {
+    use crate::*;
+
+    /// two vaults with different collateral currencies
+    #[tokio::test]
+    async fn e2e_btc_vaults_test() {
+        let _ = env_logger::builder().try_init();
+
+        let (bitcoin, ggx) = join!(start_host_btc(), start_ggx(vecs!["--alice"]));
+        setup_ksm_collateral(&ggx).await;
+        set_oracle_exchange_rates(&ggx).await;
+
+        let cfgs = [
+            VaultConfig::new("alice").with_collateral("GGXT", COLLATERAL),
+            VaultConfig::new("bob").with_collateral("KSM", COLLATERAL),
+        ];
+        let _vaults = start_vaults(&bitcoin, &ggx, &cfgs).await;
+
+        let alice = vault_id(
+            dev::alice().public_key().to_account_id(),
+            CurrencyId::Token(TokenSymbol::GGXT),
+        );
+        let bob = vault_id(
+            dev::bob().public_key().to_account_id(),
+            CurrencyId::Token(TokenSymbol::KSM),
+        );
+
+        for id in [&alice, &bob] {
+            assert!(!ggx.vault_registry_is_liquidated(id.clone()).await);
+            assert_eq!(ggx.vault_registry_collateral(id.clone()).await, COLLATERAL);
+            assert_eq!(ggx.vault_registry_redeemable_tokens(id.clone()).await, 0);
+            assert_eq!(ggx.vault_registry_collateralization(id.clone()).await, None);
+            // the collateral is staked in the vault reward pool
+            assert_eq!(ggx.reward_vault_stake(id.clone()).await, COLLATERAL);
+        }
+        assert!(ggx.vault_registry_get_vaults().await.len() >= 2);
+
+        // a BTC costs twice as many planck of KSM as of GGXT, i.e. KSM is worth half as much,
+        // so the same KSM collateral backs half as much KBTC
+        let alice_issuable = ggx.vault_registry_issuable_tokens(alice.clone()).await;
+        let bob_issuable = ggx.vault_registry_issuable_tokens(bob.clone()).await;
+        assert!(alice_issuable > 0);
+        assert_eq!(bob_issuable, alice_issuable / 2);
+
+        ggx.vault_registry_deposit_collateral(dev::bob(), bob.clone(), COLLATERAL)
+            .await;
+        assert_eq!(
+            ggx.vault_registry_collateral(bob.clone()).await,
+            COLLATERAL * 2
+        );
+        assert_eq!(
+            ggx.vault_registry_issuable_tokens(bob.clone()).await,
+            alice_issuable
+        );
+        assert_eq!(ggx.reward_vault_stake(bob.clone()).await, COLLATERAL * 2);
+
+        ggx.vault_registry_withdraw_collateral(dev::bob(), bob.clone(), COLLATERAL)
+            .await;
+        assert_eq!(ggx.vault_registry_collateral(bob.clone()).await, COLLATERAL);
+        assert_eq!(ggx.reward_vault_stake(bob).await, COLLATERAL);
+    }
+}
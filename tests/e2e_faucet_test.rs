@@ -0,0 +1,58 @@
+use std::time::Duration;
+use subxt_signer::sr25519::dev;
+use testutil::containers::ggx::start_ggx;
+use testutil::containers::ggx::tokens_pallet::TokensPallet;
+use testutil::containers::interbtc_clients::{start_faucet, FaucetConfig};
+use testutil::metadata::ggx::runtime_types::interbtc_primitives::{CurrencyId, TokenSymbol};
+use testutil::vecs;
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// the faucet pays from its own orml_tokens balance
+const FAUCET_BALANCE: u128 = 1_000_000_000_000_000;
+
+/// Allowance config of the faucet, `amount` is in whole tokens.
+fn write_allowance_config() -> String {
+    let config = serde_json::json!({
+        "max_fundable_client_balance": FAUCET_BALANCE,
+        "faucet_cooldown_hours": 6,
+        "user_allowances": [{ "symbol": "KSM", "amount": 1 }],
+        "vault_allowances": [{ "symbol": "KSM", "amount": 10 }],
+    });
+    let path = std::env::temp_dir().join(format!(
+        "faucet-allowance-config-{}.json",
+        std::process::id()
+    ));
+    std::fs::write(&path, config.to_string()).expect("cannot write allowance config");
+    path.to_str().unwrap().to_string()
+}
+
+#[cfg(test)]
+mod e2e_faucet_test {
+    use crate::*;
+
+    /// Dave, who has no KSM, gets his user allowance from the faucet
+    #[tokio::test]
+    async fn e2e_faucet_test() {
+        let _ = env_logger::builder().try_init();
+
+        let ggx = start_ggx(vecs!["--alice"]).await;
+        let ksm = CurrencyId::Token(TokenSymbol::KSM);
+        let cfg = FaucetConfig::default().with_allowance_config_file(&write_allowance_config());
+        ggx.tokens_set_balance(cfg.account_id(), ksm.clone(), FAUCET_BALANCE, 0)
+            .await;
+
+        let faucet = start_faucet(&ggx, &cfg).await;
+        let dave = dev::dave().public_key().to_account_id();
+        assert_eq!(ggx.tokens_free_balance(dave.clone(), ksm.clone()).await, 0);
+
+        let received = faucet.client().fund_user(&ggx, &dave, &ksm, TIMEOUT).await;
+        assert!(received > 0);
+        assert_eq!(ggx.tokens_free_balance(dave, ksm.clone()).await, received);
+        // transaction fees are paid in the native currency, not in KSM
+        assert_eq!(
+            ggx.tokens_free_balance(faucet.account_id(), ksm).await,
+            FAUCET_BALANCE - received
+        );
+    }
+}
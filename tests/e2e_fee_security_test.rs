@@ -0,0 +1,71 @@
+use std::time::Duration;
+use testutil::containers::ggx::fee_pallet::FeePallet;
+use testutil::containers::ggx::security_pallet::SecurityPallet;
+use testutil::containers::ggx::vault_registry_pallet::FIXED_ONE;
+use testutil::containers::ggx::{start_ggx, GgxNodeContainer, SubstrateApi};
+use testutil::metadata;
+use testutil::metadata::ggx::runtime_types::sp_arithmetic::fixed_point::FixedU128;
+use testutil::vecs;
+use testutil::wait::{wait_until, DEFAULT_POLL_INTERVAL};
+
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+async fn block_number(ggx: &GgxNodeContainer) -> u32 {
+    ggx.api()
+        .storage()
+        .at_latest()
+        .await
+        .expect("cannot get storage at latest")
+        .fetch_or_default(&metadata::ggx::storage().system().number())
+        .await
+        .expect("cannot get block number")
+}
+
+/// wait until GGX produces `n` more blocks
+async fn wait_for_blocks(ggx: &GgxNodeContainer, n: u32) {
+    let start = block_number(ggx).await;
+    wait_until(
+        TIMEOUT,
+        DEFAULT_POLL_INTERVAL,
+        || block_number(ggx),
+        |number| *number >= start + n,
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod e2e_fee_security_test {
+    use crate::*;
+
+    /// fee and security settings changed with sudo take effect
+    #[tokio::test]
+    async fn e2e_fee_security_test() {
+        let _ = env_logger::builder().try_init();
+
+        let ggx = start_ggx(vecs!["--alice"]).await;
+
+        // 1%
+        let fee = FixedU128(FIXED_ONE / 100);
+        assert_ne!(ggx.fee_issue_fee().await.0, fee.0);
+        ggx.fee_set_issue_fee(fee.clone()).await;
+        assert_eq!(ggx.fee_issue_fee().await.0, fee.0);
+
+        // the active block counter runs by default
+        assert!(!ggx.security_is_deactivated().await);
+        let count = ggx.security_active_block_count().await;
+        wait_for_blocks(&ggx, 1).await;
+        assert!(ggx.security_active_block_count().await > count);
+
+        // a deactivated counter is frozen while blocks are still produced
+        ggx.security_set_active(false).await;
+        assert!(ggx.security_is_deactivated().await);
+        let frozen = ggx.security_active_block_count().await;
+        wait_for_blocks(&ggx, 3).await;
+        assert_eq!(ggx.security_active_block_count().await, frozen);
+
+        ggx.security_set_active(true).await;
+        assert!(!ggx.security_is_deactivated().await);
+        wait_for_blocks(&ggx, 1).await;
+        assert!(ggx.security_active_block_count().await > frozen);
+    }
+}
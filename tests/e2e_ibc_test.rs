@@ -10,12 +10,15 @@ mod ibc {
     use testcontainers::core::{CmdWaitFor, WaitFor};
     use testcontainers::runners::AsyncRunner;
     use testcontainers::RunnableImage;
+    use testutil::assert_diff;
+    use testutil::balances::{BalanceKey, BalanceSnapshot, Chains};
     use testutil::containers::cosmos::start_cosmos;
     use testutil::containers::ggx::assets_pallet::AssetsPallet;
 
     use testutil::containers::ggx::{start_ggx, GgxNodeContainer};
     use testutil::containers::hermes::{HermesArgs, HermesContainer, HermesImage};
 
+    use testutil::images;
     use testutil::vecs;
 
     fn init() {
@@ -49,6 +52,7 @@ echo ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
 echo STARTING HERMES
 hermes --config config/cos_sub.toml start
 "#];
+            images::require(&image).await;
             let image: RunnableImage<HermesImage> = image.into();
             let image = image.with_args(HermesArgs {
                 args
@@ -56,7 +60,7 @@ hermes --config config/cos_sub.toml start
                 .with_network("host")
                 .with_container_name("hermes");
 
-            HermesContainer(image.start().await)
+            HermesContainer::from(image.start().await)
         }).await.expect("hermes timed out")
     }
 
@@ -92,10 +96,6 @@ hermes --config config/cos_sub.toml start
                 Duration::from_secs(60), // timeout
             )
             .await;
-
-        // wait for auto relay by hermes, about 30s
-        log::info!("Waiting 30 sec...");
-        tokio::time::sleep(Duration::from_secs(30)).await;
     }
 
     async fn withdraw_ggx_to_cosmos(
@@ -134,16 +134,14 @@ hermes --config config/cos_sub.toml start
                 Duration::from_secs(60), // timeout
             )
             .await;
-
-        // wait 30 sec
-        log::info!("Waiting 30 sec...");
-        tokio::time::sleep(Duration::from_secs(30)).await;
     }
 
     const BOB_GGX_ADDRESS: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
     const ALICE_COSMOS_ADDRESS: &str = "cosmos1xh2jvz9ecty8qdctlgscmys2dr5gz729k0l7x4";
     const GGX_ASSET_A: u32 = 666;
     const GGX_ASSET_A_NAME: &str = "ERT";
+    // hermes relays packets in about 30s
+    const RELAY_TIMEOUT: Duration = Duration::from_secs(90);
 
     #[tokio::test]
     async fn test_cosmos_ggx_deposit_withdraw_sunny_day() {
@@ -159,16 +157,18 @@ hermes --config config/cos_sub.toml start
 
         log::info!("Starting the test...");
 
-        let init_alice_cosmos_balances = cosmos
-            .get_bank_balances_by_address(ALICE_COSMOS_ADDRESS)
-            .await
-            .expect("cannot get alice balance");
-
         log::info!("Creating cross asset");
         alice
             .asset_force_create(dev::bob(), GGX_ASSET_A, 10_u128)
             .await;
 
+        let chains = Chains::default().ggx(&alice).cosmos(&cosmos);
+        let balance_keys = vec![
+            BalanceKey::cosmos(ALICE_COSMOS_ADDRESS, GGX_ASSET_A_NAME),
+            BalanceKey::Asset(dev::bob().public_key().into(), GGX_ASSET_A),
+        ];
+        let before_deposit = BalanceSnapshot::take(&chains, &balance_keys).await;
+
         // DEPOSIT COSMOS --> GGX
 
         // transfer from earth to ggx rococo
@@ -182,13 +182,23 @@ hermes --config config/cos_sub.toml start
         const BOB_DEPOSIT_AMOUNT: u128 = 999000;
         deposit_cosmos_to_ggx(&hermes, BOB_DEPOSIT_AMOUNT, GGX_ASSET_A_NAME.to_string()).await;
 
-        let current_alice_cosmos_balances = cosmos
-            .get_bank_balances_by_address(ALICE_COSMOS_ADDRESS)
-            .await
-            .expect("cannot get alice balances");
+        // wait for auto relay by hermes
+        log::info!("Waiting for Bob to receive the deposit...");
+        alice
+            .wait_for_asset_balance(
+                dev::bob(),
+                GGX_ASSET_A,
+                |balance| balance == BOB_DEPOSIT_AMOUNT,
+                RELAY_TIMEOUT,
+            )
+            .await;
 
-        // alice balance on cosmos changed!
-        assert_ne!(init_alice_cosmos_balances, current_alice_cosmos_balances);
+        // ERT moved from Cosmos:Alice to GGX:Bob
+        let after_deposit = BalanceSnapshot::take(&chains, &balance_keys).await;
+        assert_diff!(before_deposit, after_deposit, {
+            BalanceKey::cosmos(ALICE_COSMOS_ADDRESS, GGX_ASSET_A_NAME) => -(BOB_DEPOSIT_AMOUNT as i128),
+            BalanceKey::Asset(dev::bob().public_key().into(), GGX_ASSET_A) => BOB_DEPOSIT_AMOUNT,
+        });
 
         let bob_asset = alice
             .asset_get_balance(dev::bob(), GGX_ASSET_A)
@@ -209,26 +219,24 @@ hermes --config config/cos_sub.toml start
         withdraw_ggx_to_cosmos(&alice, &hermes, BOB_WITHDRAW_AMOUNT).await;
 
         // check that Bob has correct amount after we have withdrawn a bit
-        let bob_asset = alice
-            .asset_get_balance(dev::bob(), GGX_ASSET_A)
-            .await
-            .expect("unable to get Bob's GGX_CROSS_ASSET_ID asset");
-        assert_eq!(bob_asset.balance, BOB_DEPOSIT_AMOUNT - BOB_WITHDRAW_AMOUNT);
+        alice
+            .wait_for_asset_balance(
+                dev::bob(),
+                GGX_ASSET_A,
+                |balance| balance == BOB_DEPOSIT_AMOUNT - BOB_WITHDRAW_AMOUNT,
+                RELAY_TIMEOUT,
+            )
+            .await;
 
-        // check balance on Cosmos
-        let alice_balances = cosmos
-            .get_bank_balances_by_address(ALICE_COSMOS_ADDRESS)
-            .await
-            .expect("unable to get Alice balance");
-
-        let alice_ert_balance = alice_balances
-            .balances
-            .iter()
-            .find(|s| s.denom == GGX_ASSET_A_NAME)
-            .expect("invalid balance");
-        assert_eq!(
-            alice_ert_balance.amount,
-            Decimal::from_str_exact("199501000").unwrap()
-        );
+        // check balance on Cosmos, it is updated once hermes relays the packet
+        let expected = Decimal::from_str_exact("199501000").unwrap();
+        cosmos
+            .wait_for_bank_balance(
+                ALICE_COSMOS_ADDRESS,
+                GGX_ASSET_A_NAME,
+                |amount| amount == expected,
+                RELAY_TIMEOUT,
+            )
+            .await;
     }
 }